@@ -1,10 +1,11 @@
-use core::{Application, EngineBuilder, TIME};
+use core::{Application, BlendMode, EngineBuilder, TextureHandle, Transform2D, UvRect, TIME};
 use std::{env, ops::Add};
 
 use cgmath::{Vector2, Vector4};
-use log::LevelFilter;
+use log::{info, LevelFilter};
 use winit::{
     dpi::{LogicalSize, Size},
+    event::VirtualKeyCode,
     window::Icon,
 };
 
@@ -48,6 +49,9 @@ fn main() {
 struct Sandbox {
     position: Vector2<f32>,
     quads: Vec<(Vector2<f32>, Vector2<f32>, Vector4<f32>)>,
+    culling: bool,
+    icon: Option<TextureHandle>,
+    icon_rotation: f32,
 }
 
 impl Sandbox {
@@ -55,6 +59,9 @@ impl Sandbox {
         Self {
             position: Vector2::new(0.0, 0.0),
             quads: Vec::new(),
+            culling: true,
+            icon: None,
+            icon_rotation: 0.0,
         }
     }
 }
@@ -64,6 +71,11 @@ impl Application for Sandbox {
         TIME!("app.on_init");
         ctx.set_background_color(&[0.0, 0.4, 1.0, 1.0]);
 
+        self.icon = match ctx.load_texture(ICON_BYTES) {
+            Ok(texture) => Some(texture),
+            Err(e) => panic!("failed to load texture: {:?}", e),
+        };
+
         // compute quads
         let size = Vector2::new(0.075, 0.075);
         let x_count = 100;
@@ -89,12 +101,41 @@ impl Application for Sandbox {
         }
     }
 
-    fn on_update(&mut self, _: core::Context) {
+    fn on_update(&mut self, mut ctx: core::Context) {
         TIME!("app.on_update");
+
+        // half a turn per second
+        self.icon_rotation += std::f32::consts::PI * ctx.delta_time().as_secs_f32();
+
+        // toggle culling to compare the number of quads drawn
+        if ctx.is_key_just_pressed(VirtualKeyCode::F3) {
+            self.culling = !self.culling;
+            ctx.set_culling(self.culling);
+            info!("culling: {} {:?}", self.culling, ctx.render_stats());
+        }
     }
 
     fn on_render(&mut self, mut ctx: core::Context) {
         TIME!("app.on_render");
+        // the icon is drawn first but stays in front of the quads
+        if let Some(icon) = self.icon {
+            ctx.draw_texture_ex(
+                Transform2D::new(Vector2::new(0.0, 0.0), Vector2::new(1.0, 1.0))
+                    .with_rotation(self.icon_rotation)
+                    .with_z(1.0),
+                icon,
+                UvRect::FULL,
+                Vector4::new(1.0, 1.0, 1.0, 1.0),
+            );
+        }
+        // a faint glow lighting up the quads around the icon
+        ctx.draw_blended(
+            Transform2D::new(Vector2::new(0.0, 0.0), Vector2::new(2.0, 2.0)).with_z(0.5),
+            TextureHandle::WHITE,
+            UvRect::FULL,
+            Vector4::new(1.0, 0.8, 0.4, 0.2),
+            BlendMode::Additive,
+        );
         for (pos, size, color) in &self.quads {
             ctx.draw_quad(*pos, *size, *color);
         }
@@ -5,21 +5,34 @@ use std::{
     time::{Duration, Instant},
 };
 
-use cgmath::{Vector2, Vector4};
+use cgmath::{Vector2, Vector3, Vector4};
 use gameloop::GameLoop;
 use log::debug;
 use winit::{
-    dpi::{LogicalSize, Size},
-    event::{Event, VirtualKeyCode, WindowEvent},
+    dpi::{LogicalSize, PhysicalSize, Size},
+    event::{Event, ModifiersState, MouseButton, VirtualKeyCode, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     window::{Fullscreen, Icon, Window, WindowBuilder},
 };
 
 use crate::{
-    input::InputSystem,
-    render::camera::{CameraController, CameraOrthographic},
+    input::{
+        FileDropEvent, GamepadAxis, GamepadBackend, GamepadButton, GamepadEvent, GamepadId,
+        Gesture, InputMap, InputSystem, KeyChord, KeySequence,
+    },
+    render::camera::{
+        CameraController, CameraFollow, CameraId, CameraProjection, CameraSettings, CameraShake,
+        Cameras, PixelPerfect, Ray, Rect, ScalingPolicy,
+    },
+    window::WindowState,
+};
+use crate::{
+    render::{
+        AnimatedSprite, BlendMode, RenderStats, Renderer2D, SpriteSheet, TextureHandle,
+        Transform2D, UvRect,
+    },
+    TIME,
 };
-use crate::{render::Renderer2D, TIME};
 
 type Result<T> = result::Result<T, Box<dyn Error>>;
 
@@ -33,6 +46,11 @@ pub struct EngineBuilder {
     window_visible: bool,
     window_icon: Option<Icon>,
     renderer_debug: bool,
+    input_map: Option<InputMap>,
+    camera_edge_scroll: bool,
+    camera_projection: CameraProjection,
+    camera_scaling: ScalingPolicy,
+    camera_pixel_perfect: Option<PixelPerfect>,
 }
 
 impl EngineBuilder {
@@ -47,6 +65,11 @@ impl EngineBuilder {
             window_visible: true,
             window_icon: None,
             renderer_debug: false,
+            input_map: None,
+            camera_edge_scroll: false,
+            camera_projection: CameraProjection::Orthographic,
+            camera_scaling: ScalingPolicy::default(),
+            camera_pixel_perfect: None,
         }
     }
 
@@ -90,6 +113,32 @@ impl EngineBuilder {
         self
     }
 
+    pub fn with_input_map(mut self, m: InputMap) -> Self {
+        self.input_map = Some(m);
+        self
+    }
+
+    pub fn with_camera_edge_scroll(mut self, b: bool) -> Self {
+        self.camera_edge_scroll = b;
+        self
+    }
+
+    pub fn with_camera_projection(mut self, p: CameraProjection) -> Self {
+        self.camera_projection = p;
+        self
+    }
+
+    pub fn with_camera_scaling(mut self, p: ScalingPolicy) -> Self {
+        self.camera_scaling = p;
+        self
+    }
+
+    // pixel perfect orthographic camera, for pixel art
+    pub fn with_camera_pixel_perfect(mut self, p: PixelPerfect) -> Self {
+        self.camera_pixel_perfect = Some(p);
+        self
+    }
+
     pub fn build(mut self) -> Engine {
         let mut wb = WindowBuilder::new()
             .with_min_inner_size(Size::Logical(LogicalSize::new(320.0, 240.0)))
@@ -106,7 +155,21 @@ impl EngineBuilder {
             wb = wb.with_title(window_title);
         }
 
-        Engine::new(self.app, wb, self.renderer_debug)
+        let mut input = InputSystem::new();
+        if let Some(input_map) = self.input_map.take() {
+            input.set_map(input_map);
+        }
+
+        Engine::new(
+            self.app,
+            wb,
+            self.renderer_debug,
+            input,
+            self.camera_edge_scroll,
+            self.camera_projection,
+            self.camera_scaling,
+            self.camera_pixel_perfect,
+        )
     }
 }
 
@@ -116,30 +179,53 @@ pub struct Engine {
     renderer: Option<Renderer2D>,
     renderer_debug: bool,
     input: Option<InputSystem>,
+    camera_edge_scroll: bool,
+    camera_projection: CameraProjection,
+    camera_scaling: ScalingPolicy,
+    camera_pixel_perfect: Option<PixelPerfect>,
 }
 
 impl Engine {
-    fn new(app: Box<dyn Application>, wb: WindowBuilder, renderer_debug: bool) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        app: Box<dyn Application>,
+        wb: WindowBuilder,
+        renderer_debug: bool,
+        input: InputSystem,
+        camera_edge_scroll: bool,
+        camera_projection: CameraProjection,
+        camera_scaling: ScalingPolicy,
+        camera_pixel_perfect: Option<PixelPerfect>,
+    ) -> Self {
         Engine {
             app: Some(app),
             window_builder: Some(wb),
             renderer: None,
             renderer_debug,
-            input: Some(InputSystem::new()),
+            input: Some(input),
+            camera_edge_scroll,
+            camera_projection,
+            camera_scaling,
+            camera_pixel_perfect,
         }
     }
 
     pub fn run(&mut self) -> Result<()> {
         // window
         let (event_loop, window) = self.init_window()?;
-        let dimensions = window.inner_size();
+        let mut window_state = WindowState::new(window.inner_size(), window.scale_factor());
 
         // camera
-        let camera = CameraOrthographic::new(dimensions.width, dimensions.height);
-        let mut camera_controller = CameraController::new(camera);
+        let dimensions = window_state.logical_size();
+        let mut main_camera =
+            CameraController::new(self.camera_projection, dimensions.width, dimensions.height);
+        main_camera.set_edge_scroll(self.camera_edge_scroll);
+        main_camera.set_scaling_policy(self.camera_scaling);
+        main_camera.set_pixel_perfect(self.camera_pixel_perfect);
+        let mut cameras = Cameras::new(main_camera);
 
         // renderer
-        self.init_renderer(window)?;
+        self.init_renderer(window.clone())?;
         let mut renderer = self
             .renderer
             .take()
@@ -147,6 +233,8 @@ impl Engine {
 
         // input system
         let mut input = self.input.take().ok_or("Count take input")?;
+        CameraController::bind_defaults(input.map_mut());
+        let mut gamepad_backend = GamepadBackend::new();
 
         // application
         let mut app = self.app.take().ok_or("Couldnt take app")?;
@@ -159,13 +247,22 @@ impl Engine {
         // delta time
         let mut last_time = Instant::now();
 
+        let mut cursor_locked = false;
+
         // init phase
-        app.on_init(Context::new(Duration::ZERO, &mut renderer, &input));
+        app.on_init(Context::new(
+            Duration::ZERO,
+            &mut renderer,
+            &mut input,
+            &window_state,
+            &mut cameras,
+        ));
 
         debug!("start event loop");
         // event_loop.run() hijacks the main thread and calls std::process::exit when
         // done anything that has not been moved in the closure will not be dropped
         event_loop.run(move |event, _, control_flow| {
+            window_state.on_event(&event);
             input.on_event(&event);
             renderer.on_event(&event);
 
@@ -176,9 +273,66 @@ impl Engine {
                 } => {
                     *control_flow = ControlFlow::Exit;
                 }
+                Event::WindowEvent {
+                    event: WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. },
+                    ..
+                } => {
+                    // the camera follows the window size, in logical pixels so
+                    // that the projection stays the same when moving to a
+                    // monitor with another scale
+                    let dimensions = window_state.logical_size();
+                    cameras.resize(dimensions.width, dimensions.height);
+                }
+                Event::WindowEvent {
+                    event: WindowEvent::HoveredFile(path),
+                    ..
+                } => {
+                    let ctx = Context::new(
+                        Duration::ZERO,
+                        &mut renderer,
+                        &mut input,
+                        &window_state,
+                        &mut cameras,
+                    );
+                    let position = ctx.mouse_world_position();
+                    app.on_file_drop(ctx, FileDropEvent::Hovered { path, position });
+                }
+                Event::WindowEvent {
+                    event: WindowEvent::DroppedFile(path),
+                    ..
+                } => {
+                    let ctx = Context::new(
+                        Duration::ZERO,
+                        &mut renderer,
+                        &mut input,
+                        &window_state,
+                        &mut cameras,
+                    );
+                    let position = ctx.mouse_world_position();
+                    app.on_file_drop(ctx, FileDropEvent::Dropped { path, position });
+                }
+                Event::WindowEvent {
+                    event: WindowEvent::HoveredFileCancelled,
+                    ..
+                } => {
+                    app.on_file_drop(
+                        Context::new(
+                            Duration::ZERO,
+                            &mut renderer,
+                            &mut input,
+                            &window_state,
+                            &mut cameras,
+                        ),
+                        FileDropEvent::HoverCancelled,
+                    );
+                }
                 Event::MainEventsCleared => {
                     // NOTE: the MainEventsCleared event "will be emitted when all input events
                     //       have been processed and redraw processing is about to begin".
+                    while let Some(event) = gamepad_backend.next_event() {
+                        input.on_gamepad_event(event);
+                    }
+
                     for action in game_loop.actions() {
                         // delta time
                         let current_time = Instant::now();
@@ -191,11 +345,26 @@ impl Engine {
                             gameloop::FrameAction::Tick => {
                                 TIME!("gameloop::FrameAction::Tick");
 
-                                app.on_update(Context::new(delta_time, &mut renderer, &input));
-                                camera_controller.on_update(
-                                    Context::new(delta_time, &mut renderer, &input),
+                                app.on_update(Context::new(
                                     delta_time,
-                                );
+                                    &mut renderer,
+                                    &mut input,
+                                    &window_state,
+                                    &mut cameras,
+                                ));
+                                cameras.on_update(&input, window_state.scale_factor(), delta_time);
+
+                                // hide and grab the cursor while the camera uses
+                                // the mouse motion
+                                if cameras.is_cursor_locked() != cursor_locked {
+                                    cursor_locked = cameras.is_cursor_locked();
+                                    if let Err(e) = window.set_cursor_grab(cursor_locked) {
+                                        debug!("could not grab the cursor: {:?}", e);
+                                    }
+                                    window.set_cursor_visible(!cursor_locked);
+                                }
+
+                                input.end_tick();
                             }
                             gameloop::FrameAction::Render { .. } => {
                                 TIME!("gameloop::FrameAction::Render");
@@ -205,13 +374,19 @@ impl Engine {
                                     return;
                                 }
 
-                                app.on_render(Context::new(delta_time, &mut renderer, &input));
-
-                                renderer.end_frame(camera_controller.view_projection_matrix());
+                                app.on_render(Context::new(
+                                    delta_time,
+                                    &mut renderer,
+                                    &mut input,
+                                    &window_state,
+                                    &mut cameras,
+                                ));
+
+                                renderer.set_low_resolution(cameras.main().low_resolution_target());
+                                renderer.end_frame(&cameras.views());
                             }
                         }
                     }
-                    input.reset();
                 }
                 _ => {}
             }
@@ -244,15 +419,25 @@ impl Engine {
 pub struct Context<'a> {
     delta_time: Duration,
     renderer: &'a mut Renderer2D,
-    input: &'a InputSystem,
+    input: &'a mut InputSystem,
+    window: &'a WindowState,
+    cameras: &'a mut Cameras,
 }
 
 impl<'a> Context<'a> {
-    fn new(delta: Duration, renderer: &'a mut Renderer2D, input: &'a InputSystem) -> Self {
+    fn new(
+        delta: Duration,
+        renderer: &'a mut Renderer2D,
+        input: &'a mut InputSystem,
+        window: &'a WindowState,
+        cameras: &'a mut Cameras,
+    ) -> Self {
         Self {
             delta_time: delta,
             renderer,
             input,
+            window,
+            cameras,
         }
     }
 
@@ -260,14 +445,189 @@ impl<'a> Context<'a> {
         self.delta_time
     }
 
+    pub fn scale_factor(&self) -> f64 {
+        self.window.scale_factor()
+    }
+
+    pub fn window_size(&self) -> LogicalSize<f32> {
+        self.window.logical_size()
+    }
+
+    pub fn window_physical_size(&self) -> PhysicalSize<u32> {
+        self.window.physical_size()
+    }
+
     pub fn set_background_color(&mut self, c: &[f32; 4]) {
         self.renderer.set_background_color(c)
     }
 
+    // quads outside of the cameras are culled by default
+    pub fn set_culling(&mut self, b: bool) {
+        self.renderer.set_culling(b)
+    }
+
+    // what was drawn during the last frame
+    pub fn render_stats(&self) -> RenderStats {
+        self.renderer.stats()
+    }
+
     pub fn draw_quad(&mut self, position: Vector2<f32>, size: Vector2<f32>, color: Vector4<f32>) {
         self.renderer.draw_quad(position, size, color)
     }
 
+    // draw a quad rotated around its pivot, on the layer of the transform.
+    // Quads with a greater z are drawn in front
+    pub fn draw_quad_ex(&mut self, transform: Transform2D, color: Vector4<f32>) {
+        self.renderer
+            .draw_quad_ex(transform, color, TextureHandle::WHITE, UvRect::FULL)
+    }
+
+    // draw a rotated area of a texture, for example a frame of a sprite sheet
+    pub fn draw_texture_ex(
+        &mut self,
+        transform: Transform2D,
+        texture: TextureHandle,
+        uv: UvRect,
+        tint: Vector4<f32>,
+    ) {
+        self.renderer.draw_quad_ex(transform, tint, texture, uv)
+    }
+
+    // draw a rotated area of a texture with a blend mode other than the
+    // default alpha blending, for example additive lights
+    pub fn draw_blended(
+        &mut self,
+        transform: Transform2D,
+        texture: TextureHandle,
+        uv: UvRect,
+        tint: Vector4<f32>,
+        blend: BlendMode,
+    ) {
+        self.renderer
+            .draw_quad_blended(transform, tint, texture, uv, blend)
+    }
+
+    // the texture is multiplied by the tint color, use a white tint to draw
+    // the texture as is
+    pub fn draw_textured_quad(
+        &mut self,
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        texture: TextureHandle,
+        tint: Vector4<f32>,
+    ) {
+        self.renderer
+            .draw_textured_quad(position, size, texture, tint)
+    }
+
+    // draw a frame of a sprite sheet
+    pub fn draw_sprite(
+        &mut self,
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        sheet: &SpriteSheet,
+        frame: usize,
+        tint: Vector4<f32>,
+    ) {
+        if let Some(uv) = sheet.frame(frame) {
+            self.renderer
+                .draw_sprite(position, size, sheet.texture(), uv, tint)
+        }
+    }
+
+    // draw the current frame of an animation, with its flips
+    pub fn draw_animated_sprite(
+        &mut self,
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        sheet: &SpriteSheet,
+        sprite: &AnimatedSprite,
+        tint: Vector4<f32>,
+    ) {
+        self.renderer
+            .draw_sprite(position, size, sheet.texture(), sprite.uv(sheet), tint)
+    }
+
+    // draw any area of a texture, flipped with UvRect::flipped()
+    pub fn draw_texture_region(
+        &mut self,
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        texture: TextureHandle,
+        uv: UvRect,
+        tint: Vector4<f32>,
+    ) {
+        self.renderer.draw_sprite(position, size, texture, uv, tint)
+    }
+
+    // load a PNG image, for example from include_bytes!()
+    pub fn load_texture(&mut self, png_bytes: &[u8]) -> Result<TextureHandle> {
+        self.renderer.load_texture(png_bytes)
+    }
+
+    // quads drawn on a layer are only seen by the cameras whose layer mask
+    // contains it, draw_quad draws on layer 0 and the _ex calls on the layer
+    // of their transform
+    pub fn draw_quad_on_layer(
+        &mut self,
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        color: Vector4<f32>,
+        layer: u32,
+    ) {
+        self.renderer
+            .draw_quad_on_layer(position, size, color, layer)
+    }
+
+    // Inputs consumed during a tick are seen as released by everything reading
+    // them afterwards, including the camera. Consume them from the highest
+    // priority layer (menu, console) before updating the game.
+    pub fn consume_key(&mut self, key: VirtualKeyCode) {
+        self.input.consume_key(key)
+    }
+
+    pub fn consume_keyboard(&mut self) {
+        self.input.consume_keyboard()
+    }
+
+    pub fn consume_mouse_button(&mut self, button: MouseButton) {
+        self.input.consume_mouse_button(button)
+    }
+
+    pub fn consume_scroll(&mut self) {
+        self.input.consume_scroll()
+    }
+
+    // consume the cursor position and the mouse motion
+    pub fn consume_cursor(&mut self) {
+        self.input.consume_cursor()
+    }
+
+    // consume the mouse buttons, the scroll and the cursor
+    pub fn consume_mouse(&mut self) {
+        self.input.consume_mouse()
+    }
+
+    pub fn consume_touch(&mut self) {
+        self.input.consume_touch()
+    }
+
+    pub fn consume_gamepad(&mut self) {
+        self.input.consume_gamepad()
+    }
+
+    pub fn consume_shortcuts(&mut self) {
+        self.input.consume_shortcuts()
+    }
+
+    pub fn consume_text(&mut self) {
+        self.input.consume_text()
+    }
+
+    pub fn consume_all_input(&mut self) {
+        self.input.consume_all()
+    }
+
     pub fn is_key_pressed(&self, key: VirtualKeyCode) -> bool {
         self.input.is_key_pressed(key)
     }
@@ -276,12 +636,271 @@ impl<'a> Context<'a> {
         self.input.is_key_released(key)
     }
 
-    pub fn mouse_scoll_x(&self) -> f32 {
-        self.input.mouse_scoll_x()
+    pub fn is_key_just_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.input.is_key_just_pressed(key)
+    }
+
+    pub fn is_key_just_released(&self, key: VirtualKeyCode) -> bool {
+        self.input.is_key_just_released(key)
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.input.modifiers()
+    }
+
+    // register a chord such as Ctrl+S, see is_shortcut_triggered()
+    pub fn register_chord(&mut self, name: &str, chord: KeyChord) {
+        self.input.register_chord(name, chord)
+    }
+
+    // register a timed sequence of keys, see is_shortcut_triggered()
+    pub fn register_sequence(&mut self, name: &str, sequence: KeySequence) {
+        self.input.register_sequence(name, sequence)
+    }
+
+    pub fn unregister_shortcut(&mut self, name: &str) {
+        self.input.unregister_shortcut(name)
+    }
+
+    // true during the tick following the completion of the chord or sequence
+    pub fn is_shortcut_triggered(&self, name: &str) -> bool {
+        self.input.is_shortcut_triggered(name)
+    }
+
+    pub fn is_action_pressed(&self, name: &str) -> bool {
+        self.input.is_action_pressed(name)
+    }
+
+    pub fn is_action_just_pressed(&self, name: &str) -> bool {
+        self.input.is_action_just_pressed(name)
+    }
+
+    pub fn is_action_just_released(&self, name: &str) -> bool {
+        self.input.is_action_just_released(name)
+    }
+
+    pub fn axis(&self, name: &str) -> f32 {
+        self.input.axis(name)
+    }
+
+    pub fn input_map(&self) -> &InputMap {
+        self.input.map()
+    }
+
+    // bindings can be changed at runtime, for example from a settings menu
+    pub fn input_map_mut(&mut self) -> &mut InputMap {
+        self.input.map_mut()
+    }
+
+    // characters typed since the last tick, only collected when text input is
+    // enabled
+    pub fn text_input(&self) -> &str {
+        self.input.text_input()
+    }
+
+    // editing keys (backspace, arrows, ...) pressed or repeated since the last
+    // tick, in order
+    pub fn text_edit_keys(&self) -> &[VirtualKeyCode] {
+        self.input.text_edit_keys()
+    }
+
+    pub fn set_text_input_enabled(&mut self, b: bool) {
+        self.input.text_mut().set_enabled(b)
+    }
+
+    pub fn is_text_input_enabled(&self) -> bool {
+        self.input.text().is_enabled()
+    }
+
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.input.is_mouse_button_pressed(button)
+    }
+
+    pub fn is_mouse_button_released(&self, button: MouseButton) -> bool {
+        self.input.is_mouse_button_released(button)
+    }
+
+    pub fn is_mouse_button_just_pressed(&self, button: MouseButton) -> bool {
+        self.input.is_mouse_button_just_pressed(button)
+    }
+
+    pub fn is_mouse_button_just_released(&self, button: MouseButton) -> bool {
+        self.input.is_mouse_button_just_released(button)
+    }
+
+    pub fn is_cursor_in_window(&self) -> bool {
+        self.input.is_cursor_in_window()
+    }
+
+    pub fn gamepads(&self) -> Vec<GamepadId> {
+        self.input.gamepads().connected()
+    }
+
+    pub fn is_gamepad_connected(&self, id: GamepadId) -> bool {
+        self.input.gamepads().is_connected(id)
+    }
+
+    // gamepad events (connection, disconnection, ...) received since the last
+    // tick
+    pub fn gamepad_events(&self) -> impl Iterator<Item = GamepadEvent> + '_ {
+        self.input.gamepad_events()
+    }
+
+    pub fn is_gamepad_button_pressed(&self, id: GamepadId, button: GamepadButton) -> bool {
+        self.input.is_gamepad_button_pressed(id, button)
+    }
+
+    pub fn is_gamepad_button_just_pressed(&self, id: GamepadId, button: GamepadButton) -> bool {
+        self.input.is_gamepad_button_just_pressed(id, button)
+    }
+
+    pub fn is_gamepad_button_just_released(&self, id: GamepadId, button: GamepadButton) -> bool {
+        self.input.is_gamepad_button_just_released(id, button)
+    }
+
+    pub fn gamepad_button_value(&self, id: GamepadId, button: GamepadButton) -> f32 {
+        self.input.gamepad_button_value(id, button)
+    }
+
+    pub fn gamepad_axis(&self, id: GamepadId, axis: GamepadAxis) -> f32 {
+        self.input.gamepad_axis(id, axis)
+    }
+
+    pub fn set_gamepad_dead_zones(&mut self, stick: f32, trigger: f32) {
+        self.input.gamepads_mut().set_dead_zones(stick, trigger)
+    }
+
+    // cursor position in logical window pixels, origin at the top-left corner
+    pub fn mouse_position(&self) -> Vector2<f32> {
+        let position = self
+            .input
+            .cursor_position()
+            .to_logical::<f32>(self.window.scale_factor());
+        Vector2::new(position.x, position.y)
+    }
+
+    // cursor position unprojected through the camera view-projection
+    pub fn mouse_world_position(&self) -> Vector2<f32> {
+        self.cameras.main().screen_to_world(self.mouse_position())
+    }
+
+    // position on the z = 0 plane under a position in logical window pixels
+    pub fn screen_to_world(&self, position: Vector2<f32>) -> Vector2<f32> {
+        self.cameras.main().screen_to_world(position)
+    }
+
+    // position in logical window pixels of a world position, None when it is
+    // behind the camera
+    pub fn world_to_screen(&self, position: Vector3<f32>) -> Option<Vector2<f32>> {
+        self.cameras.main().world_to_screen(position)
+    }
+
+    // ray going from the camera through a position in logical window pixels
+    pub fn pick_ray(&self, position: Vector2<f32>) -> Ray {
+        self.cameras.main().pick_ray(position)
+    }
+
+    pub fn camera_position(&self) -> Vector3<f32> {
+        self.cameras.main().position()
+    }
+
+    // make the camera follow a world position, call it every tick with the
+    // latest position of the target
+    pub fn camera_follow(&mut self, target: Vector2<f32>) {
+        self.cameras.main_mut().set_follow_target(target)
+    }
+
+    pub fn set_camera_follow(&mut self, follow: CameraFollow) {
+        self.cameras.main_mut().set_follow(follow)
+    }
+
+    pub fn stop_camera_follow(&mut self) {
+        self.cameras.main_mut().stop_follow()
+    }
+
+    // shake the camera, amount is the trauma added (between 0.0 and 1.0), the
+    // shake fades out as the trauma decays
+    pub fn camera_shake(&mut self, amount: f32) {
+        self.cameras.main_mut().add_shake(amount)
+    }
+
+    pub fn set_camera_shake(&mut self, settings: CameraShake) {
+        self.cameras.main_mut().set_shake(settings)
+    }
+
+    // area of the z = 0 plane visible on screen
+    pub fn visible_world_rect(&self) -> Option<Rect> {
+        self.cameras.main().visible_rect()
+    }
+
+    // pixel perfect mode of the main camera, None to go back to a smooth
+    // camera
+    pub fn set_camera_pixel_perfect(&mut self, pixel_perfect: Option<PixelPerfect>) {
+        self.cameras.main_mut().set_pixel_perfect(pixel_perfect)
+    }
+
+    // Cameras other than the main one are not driven by the input, move them
+    // with camera() or make them follow a target. The methods above apply to
+    // the main camera.
+    pub fn add_camera(
+        &mut self,
+        projection: CameraProjection,
+        settings: CameraSettings,
+    ) -> CameraId {
+        let size = self.window.logical_size();
+        let camera = CameraController::new(projection, size.width, size.height);
+        self.cameras.add(camera, settings)
+    }
+
+    pub fn remove_camera(&mut self, id: CameraId) {
+        self.cameras.remove(id);
+    }
+
+    pub fn camera(&mut self, id: CameraId) -> Option<&mut CameraController> {
+        self.cameras.get_mut(id)
+    }
+
+    pub fn camera_settings(&self, id: CameraId) -> Option<CameraSettings> {
+        self.cameras.settings(id)
+    }
+
+    pub fn set_camera_settings(&mut self, id: CameraId, settings: CameraSettings) {
+        self.cameras.set_settings(id, settings)
+    }
+
+    // raw mouse motion accumulated since the last tick, keeps being reported
+    // when the cursor is grabbed
+    pub fn mouse_motion(&self) -> Vector2<f32> {
+        self.input.mouse_motion()
+    }
+
+    // scroll accumulated since the last tick, in lines (mouse wheel notches)
+    pub fn mouse_scroll_lines(&self) -> Vector2<f32> {
+        self.input.mouse_scroll_lines()
+    }
+
+    // scroll accumulated since the last tick, in logical pixels (touchpads)
+    pub fn mouse_scroll_pixels(&self) -> Vector2<f32> {
+        self.input.mouse_scroll_pixels() / self.window.scale_factor() as f32
+    }
+
+    // id and position, in logical window pixels, of the fingers touching the
+    // window
+    pub fn touches(&self) -> Vec<(u64, Vector2<f32>)> {
+        let scale_factor = self.window.scale_factor() as f32;
+        self.input
+            .fingers()
+            .map(|(id, position)| (id, position / scale_factor))
+            .collect()
     }
 
-    pub fn mouse_scoll_y(&self) -> f32 {
-        self.input.mouse_scoll_y()
+    // gestures recognized during this tick, in logical window pixels
+    pub fn gestures(&self) -> Vec<Gesture> {
+        self.input
+            .gestures()
+            .iter()
+            .map(|g| g.to_logical(self.window.scale_factor()))
+            .collect()
     }
 }
 
@@ -289,4 +908,6 @@ pub trait Application {
     fn on_init(&mut self, ctx: Context);
     fn on_update(&mut self, ctx: Context);
     fn on_render(&mut self, ctx: Context);
+    // called when a file is hovered, dropped or the hover is cancelled
+    fn on_file_drop(&mut self, _ctx: Context, _event: FileDropEvent) {}
 }
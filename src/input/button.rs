@@ -0,0 +1,122 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use winit::event::ElementState;
+
+// ButtonState tracks the latest state of a set of buttons (keys, mouse
+// buttons) along with the transitions that happened since the last tick.
+//
+// Transitions are only cleared by end_tick() so that they are seen by exactly
+// one tick, no matter how many frames or events happen in between.
+pub struct ButtonState<T> {
+    current: HashMap<T, ElementState>,
+    just_pressed: HashSet<T>,
+    just_released: HashSet<T>,
+}
+
+impl<T: Copy + Eq + Hash> ButtonState<T> {
+    pub fn new() -> Self {
+        Self {
+            current: HashMap::new(),
+            just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
+        }
+    }
+
+    pub fn on_input(&mut self, button: T, state: ElementState) {
+        let previous = self.current.insert(button, state);
+        match state {
+            // ignore key repeats
+            ElementState::Pressed if previous != Some(ElementState::Pressed) => {
+                self.just_pressed.insert(button);
+            }
+            ElementState::Released if previous == Some(ElementState::Pressed) => {
+                self.just_released.insert(button);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        for (button, state) in self.current.iter_mut() {
+            if *state == ElementState::Pressed {
+                *state = ElementState::Released;
+                self.just_released.insert(*button);
+            }
+        }
+    }
+
+    pub fn end_tick(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+
+    pub fn is_pressed(&self, button: T) -> bool {
+        self.current.get(&button) == Some(&ElementState::Pressed)
+    }
+
+    pub fn is_released(&self, button: T) -> bool {
+        self.current.get(&button) == Some(&ElementState::Released)
+    }
+
+    pub fn is_just_pressed(&self, button: T) -> bool {
+        self.just_pressed.contains(&button)
+    }
+
+    pub fn is_just_released(&self, button: T) -> bool {
+        self.just_released.contains(&button)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::event::{MouseButton, VirtualKeyCode};
+
+    use super::*;
+
+    #[test]
+    fn just_pressed_lasts_one_tick() {
+        let mut buttons = ButtonState::new();
+        buttons.on_input(VirtualKeyCode::A, ElementState::Pressed);
+        assert!(buttons.is_just_pressed(VirtualKeyCode::A));
+
+        // several frames without a tick must not lose the transition
+        buttons.on_input(VirtualKeyCode::A, ElementState::Pressed);
+        assert!(buttons.is_just_pressed(VirtualKeyCode::A));
+
+        buttons.end_tick();
+        assert!(!buttons.is_just_pressed(VirtualKeyCode::A));
+        assert!(buttons.is_pressed(VirtualKeyCode::A));
+    }
+
+    #[test]
+    fn press_and_release_within_one_tick() {
+        let mut buttons = ButtonState::new();
+        buttons.on_input(VirtualKeyCode::A, ElementState::Pressed);
+        buttons.on_input(VirtualKeyCode::A, ElementState::Released);
+        assert!(buttons.is_just_pressed(VirtualKeyCode::A));
+        assert!(buttons.is_just_released(VirtualKeyCode::A));
+        assert!(!buttons.is_pressed(VirtualKeyCode::A));
+    }
+
+    #[test]
+    fn untouched_key_is_not_released() {
+        let buttons = ButtonState::<VirtualKeyCode>::new();
+        assert!(!buttons.is_pressed(VirtualKeyCode::A));
+        assert!(!buttons.is_released(VirtualKeyCode::A));
+        assert!(!buttons.is_just_released(VirtualKeyCode::A));
+    }
+
+    #[test]
+    fn release_all_marks_pressed_buttons_released() {
+        let mut buttons = ButtonState::new();
+        buttons.on_input(MouseButton::Left, ElementState::Pressed);
+        buttons.end_tick();
+        buttons.release_all();
+        assert!(buttons.is_just_released(MouseButton::Left));
+        assert!(buttons.is_released(MouseButton::Left));
+        assert!(!buttons.is_pressed(MouseButton::Left));
+    }
+}
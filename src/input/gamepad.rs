@@ -0,0 +1,397 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use gilrs::{Axis, Button, EventType, Gilrs, GilrsBuilder};
+use log::{debug, error};
+use winit::event::ElementState;
+
+use super::button::ButtonState;
+
+const DEFAULT_STICK_DEAD_ZONE: f32 = 0.15;
+const DEFAULT_TRIGGER_DEAD_ZONE: f32 = 0.05;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GamepadId(pub usize);
+
+// GamepadEvent is the engine representation of a gamepad event. It is
+// decoupled from gilrs so that it can be created without any hardware.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GamepadEvent {
+    Connected(GamepadId),
+    Disconnected(GamepadId),
+    ButtonPressed(GamepadId, Button),
+    ButtonReleased(GamepadId, Button),
+    // analog buttons (triggers) report a value between 0.0 and 1.0
+    ButtonChanged(GamepadId, Button, f32),
+    // sticks report a value between -1.0 and 1.0
+    AxisChanged(GamepadId, Axis, f32),
+}
+
+impl GamepadEvent {
+    fn from_gilrs(event: gilrs::Event) -> Option<Self> {
+        let id = GamepadId(event.id.into());
+        let event = match event.event {
+            EventType::Connected => GamepadEvent::Connected(id),
+            EventType::Disconnected => GamepadEvent::Disconnected(id),
+            EventType::ButtonPressed(button, _) => GamepadEvent::ButtonPressed(id, button),
+            EventType::ButtonReleased(button, _) => GamepadEvent::ButtonReleased(id, button),
+            EventType::ButtonChanged(button, value, _) => {
+                GamepadEvent::ButtonChanged(id, button, value)
+            }
+            EventType::AxisChanged(axis, value, _) => GamepadEvent::AxisChanged(id, axis, value),
+            EventType::ButtonRepeated(..) | EventType::Dropped => return None,
+        };
+        Some(event)
+    }
+}
+
+// GamepadBackend polls gamepad events from the operating system.
+//
+// Gamepad support is optional: when gilrs cannot be initialized, the backend
+// simply never reports any event.
+pub struct GamepadBackend {
+    gilrs: Option<Gilrs>,
+    // sent in the order gilrs enumerates the gamepads
+    pending: VecDeque<GamepadEvent>,
+}
+
+impl GamepadBackend {
+    pub fn new() -> Self {
+        // dead zones are handled by Gamepads so that they can be configured
+        let gilrs = match GilrsBuilder::new().with_default_filters(false).build() {
+            Ok(gilrs) => Some(gilrs),
+            Err(e) => {
+                error!("gamepad support disabled: {}", e);
+                None
+            }
+        };
+
+        // gamepads plugged before startup do not generate a Connected event
+        let pending = gilrs
+            .iter()
+            .flat_map(|g| g.gamepads())
+            .map(|(id, _)| GamepadEvent::Connected(GamepadId(id.into())))
+            .collect();
+
+        Self { gilrs, pending }
+    }
+
+    pub fn next_event(&mut self) -> Option<GamepadEvent> {
+        if let Some(event) = self.pending.pop_front() {
+            return Some(event);
+        }
+        let gilrs = self.gilrs.as_mut()?;
+        while let Some(event) = gilrs.next_event() {
+            if let Some(event) = GamepadEvent::from_gilrs(event) {
+                return Some(event);
+            }
+        }
+        None
+    }
+}
+
+struct GamepadState {
+    // a disconnected gamepad is kept until the end of the tick so that its
+    // held buttons are seen as released
+    connected: bool,
+    buttons: ButtonState<Button>,
+    button_values: HashMap<Button, f32>,
+    axes: HashMap<Axis, f32>,
+}
+
+impl GamepadState {
+    fn new() -> Self {
+        Self {
+            connected: true,
+            buttons: ButtonState::new(),
+            button_values: HashMap::new(),
+            axes: HashMap::new(),
+        }
+    }
+}
+
+// Gamepads keeps track of the state of every connected gamepad.
+pub struct Gamepads {
+    pads: BTreeMap<GamepadId, GamepadState>,
+    events: Vec<GamepadEvent>,
+    stick_dead_zone: f32,
+    trigger_dead_zone: f32,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        Self {
+            pads: BTreeMap::new(),
+            events: Vec::new(),
+            stick_dead_zone: DEFAULT_STICK_DEAD_ZONE,
+            trigger_dead_zone: DEFAULT_TRIGGER_DEAD_ZONE,
+        }
+    }
+
+    pub fn on_event(&mut self, event: GamepadEvent) {
+        debug!("gamepad event: {:?}", event);
+        self.events.push(event);
+
+        match event {
+            GamepadEvent::Connected(id) => {
+                self.pad_mut(id).connected = true;
+            }
+            GamepadEvent::Disconnected(id) => {
+                if let Some(pad) = self.pads.get_mut(&id) {
+                    pad.connected = false;
+                    pad.buttons.release_all();
+                    pad.button_values.clear();
+                    pad.axes.clear();
+                }
+            }
+            GamepadEvent::ButtonPressed(id, button) => {
+                self.pad_mut(id)
+                    .buttons
+                    .on_input(button, ElementState::Pressed);
+            }
+            GamepadEvent::ButtonReleased(id, button) => {
+                self.pad_mut(id)
+                    .buttons
+                    .on_input(button, ElementState::Released);
+            }
+            GamepadEvent::ButtonChanged(id, button, value) => {
+                self.pad_mut(id).button_values.insert(button, value);
+            }
+            GamepadEvent::AxisChanged(id, axis, value) => {
+                self.pad_mut(id).axes.insert(axis, value);
+            }
+        }
+    }
+
+    pub fn end_tick(&mut self) {
+        self.events.clear();
+        self.pads.retain(|_, pad| pad.connected);
+        for pad in self.pads.values_mut() {
+            pad.buttons.end_tick();
+        }
+    }
+
+    pub fn set_dead_zones(&mut self, stick: f32, trigger: f32) {
+        self.stick_dead_zone = stick.clamp(0.0, 0.99);
+        self.trigger_dead_zone = trigger.clamp(0.0, 0.99);
+    }
+
+    // events received since the last tick
+    pub fn events(&self) -> &[GamepadEvent] {
+        &self.events
+    }
+
+    pub fn connected(&self) -> Vec<GamepadId> {
+        self.pads
+            .iter()
+            .filter(|(_, pad)| pad.connected)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    pub fn is_connected(&self, id: GamepadId) -> bool {
+        self.pads.get(&id).is_some_and(|pad| pad.connected)
+    }
+
+    pub fn is_button_pressed(&self, id: GamepadId, button: Button) -> bool {
+        self.pads
+            .get(&id)
+            .is_some_and(|pad| pad.buttons.is_pressed(button))
+    }
+
+    pub fn is_button_just_pressed(&self, id: GamepadId, button: Button) -> bool {
+        self.pads
+            .get(&id)
+            .is_some_and(|pad| pad.buttons.is_just_pressed(button))
+    }
+
+    pub fn is_button_just_released(&self, id: GamepadId, button: Button) -> bool {
+        self.pads
+            .get(&id)
+            .is_some_and(|pad| pad.buttons.is_just_released(button))
+    }
+
+    // analog value of a button, digital buttons report 0.0 or 1.0
+    pub fn button_value(&self, id: GamepadId, button: Button) -> f32 {
+        let pad = match self.pads.get(&id) {
+            Some(pad) => pad,
+            None => return 0.0,
+        };
+        match pad.button_values.get(&button) {
+            Some(value) => apply_dead_zone(*value, self.trigger_dead_zone),
+            None if pad.buttons.is_pressed(button) => 1.0,
+            None => 0.0,
+        }
+    }
+
+    // value of an axis with dead zones applied. Sticks use a radial dead zone
+    // so that diagonals are not snapped to the main directions.
+    pub fn axis(&self, id: GamepadId, axis: Axis) -> f32 {
+        let pad = match self.pads.get(&id) {
+            Some(pad) => pad,
+            None => return 0.0,
+        };
+        let raw = |axis| pad.axes.get(&axis).copied().unwrap_or(0.0);
+
+        match stick_axes(axis) {
+            Some((x, y)) => {
+                let magnitude = (raw(x) * raw(x) + raw(y) * raw(y)).sqrt();
+                if magnitude <= self.stick_dead_zone {
+                    return 0.0;
+                }
+                let scaled = apply_dead_zone(magnitude.min(1.0), self.stick_dead_zone);
+                raw(axis) / magnitude * scaled
+            }
+            None if matches!(axis, Axis::LeftZ | Axis::RightZ) => {
+                apply_dead_zone(raw(axis), self.trigger_dead_zone)
+            }
+            None => raw(axis),
+        }
+    }
+
+    // the following queries look at every connected gamepad, they are used
+    // to evaluate InputMap bindings
+    pub fn any_button_pressed(&self, button: Button) -> bool {
+        self.pads
+            .keys()
+            .any(|id| self.is_button_pressed(*id, button))
+    }
+
+    pub fn any_button_just_pressed(&self, button: Button) -> bool {
+        self.pads
+            .keys()
+            .any(|id| self.is_button_just_pressed(*id, button))
+    }
+
+    pub fn any_button_just_released(&self, button: Button) -> bool {
+        self.pads
+            .keys()
+            .any(|id| self.is_button_just_released(*id, button))
+    }
+
+    pub fn any_button_value(&self, button: Button) -> f32 {
+        self.pads
+            .keys()
+            .map(|id| self.button_value(*id, button))
+            .fold(0.0, f32::max)
+    }
+
+    // strongest value of an axis across gamepads, keeping its sign
+    pub fn any_axis(&self, axis: Axis) -> f32 {
+        self.pads
+            .keys()
+            .map(|id| self.axis(*id, axis))
+            .fold(0.0, |acc, v| if v.abs() > acc.abs() { v } else { acc })
+    }
+
+    fn pad_mut(&mut self, id: GamepadId) -> &mut GamepadState {
+        // gilrs does not guarantee a Connected event before the first input
+        self.pads.entry(id).or_insert_with(GamepadState::new)
+    }
+}
+
+fn stick_axes(axis: Axis) -> Option<(Axis, Axis)> {
+    match axis {
+        Axis::LeftStickX | Axis::LeftStickY => Some((Axis::LeftStickX, Axis::LeftStickY)),
+        Axis::RightStickX | Axis::RightStickY => Some((Axis::RightStickX, Axis::RightStickY)),
+        _ => None,
+    }
+}
+
+// rescale value so that the output goes from 0.0 at the edge of the dead zone
+// to 1.0 at full range
+fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    if value.abs() <= dead_zone {
+        return 0.0;
+    }
+    value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAD0: GamepadId = GamepadId(0);
+    const PAD1: GamepadId = GamepadId(1);
+
+    #[test]
+    fn connect_and_disconnect() {
+        let mut pads = Gamepads::new();
+        pads.on_event(GamepadEvent::Connected(PAD0));
+        pads.on_event(GamepadEvent::Connected(PAD1));
+        assert_eq!(pads.connected(), vec![PAD0, PAD1]);
+        assert_eq!(pads.events().len(), 2);
+
+        pads.end_tick();
+        assert!(pads.events().is_empty());
+
+        // held buttons are released for one tick when the pad goes away
+        pads.on_event(GamepadEvent::ButtonPressed(PAD0, Button::South));
+        pads.end_tick();
+        pads.on_event(GamepadEvent::Disconnected(PAD0));
+        assert_eq!(pads.connected(), vec![PAD1]);
+        assert!(!pads.is_connected(PAD0));
+        assert_eq!(pads.events(), &[GamepadEvent::Disconnected(PAD0)]);
+        assert!(!pads.any_button_pressed(Button::South));
+        assert!(pads.any_button_just_released(Button::South));
+
+        pads.end_tick();
+        assert!(!pads.any_button_just_released(Button::South));
+        assert_eq!(pads.connected(), vec![PAD1]);
+    }
+
+    #[test]
+    fn buttons_per_pad() {
+        let mut pads = Gamepads::new();
+        pads.on_event(GamepadEvent::Connected(PAD0));
+        pads.on_event(GamepadEvent::Connected(PAD1));
+        pads.on_event(GamepadEvent::ButtonPressed(PAD1, Button::South));
+
+        assert!(!pads.is_button_pressed(PAD0, Button::South));
+        assert!(pads.is_button_pressed(PAD1, Button::South));
+        assert!(pads.is_button_just_pressed(PAD1, Button::South));
+        assert!(pads.any_button_just_pressed(Button::South));
+
+        pads.end_tick();
+        assert!(pads.is_button_pressed(PAD1, Button::South));
+        assert!(!pads.is_button_just_pressed(PAD1, Button::South));
+
+        pads.on_event(GamepadEvent::ButtonReleased(PAD1, Button::South));
+        assert!(pads.is_button_just_released(PAD1, Button::South));
+    }
+
+    #[test]
+    fn stick_radial_dead_zone() {
+        let mut pads = Gamepads::new();
+        pads.set_dead_zones(0.2, 0.0);
+        pads.on_event(GamepadEvent::AxisChanged(PAD0, Axis::LeftStickX, 0.1));
+        pads.on_event(GamepadEvent::AxisChanged(PAD0, Axis::LeftStickY, 0.1));
+        assert_eq!(pads.axis(PAD0, Axis::LeftStickX), 0.0);
+
+        pads.on_event(GamepadEvent::AxisChanged(PAD0, Axis::LeftStickX, 1.0));
+        pads.on_event(GamepadEvent::AxisChanged(PAD0, Axis::LeftStickY, 0.0));
+        assert!((pads.axis(PAD0, Axis::LeftStickX) - 1.0).abs() < f32::EPSILON);
+
+        pads.on_event(GamepadEvent::AxisChanged(PAD0, Axis::LeftStickX, -0.6));
+        let expected = -(0.6 - 0.2) / 0.8;
+        assert!((pads.axis(PAD0, Axis::LeftStickX) - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn trigger_dead_zone() {
+        let mut pads = Gamepads::new();
+        pads.set_dead_zones(0.0, 0.1);
+        pads.on_event(GamepadEvent::ButtonChanged(
+            PAD0,
+            Button::RightTrigger2,
+            0.05,
+        ));
+        assert_eq!(pads.button_value(PAD0, Button::RightTrigger2), 0.0);
+
+        pads.on_event(GamepadEvent::ButtonChanged(
+            PAD0,
+            Button::RightTrigger2,
+            1.0,
+        ));
+        assert!((pads.any_button_value(Button::RightTrigger2) - 1.0).abs() < f32::EPSILON);
+    }
+}
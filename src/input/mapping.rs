@@ -0,0 +1,164 @@
+use std::{collections::BTreeMap, error::Error, fs, path::Path, result};
+
+use gilrs::{Axis, Button};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use winit::event::{MouseButton, VirtualKeyCode};
+
+type Result<T> = result::Result<T, Box<dyn Error>>;
+
+// InputBinding is a physical input that can drive an action or an axis.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(VirtualKeyCode),
+    MouseButton(MouseButton),
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
+    // gamepad bindings apply to every connected gamepad
+    GamepadButton(Button),
+    // analog bindings are pressed past half their range but do not report
+    // just pressed/released transitions
+    GamepadAxisPositive(Axis),
+    GamepadAxisNegative(Axis),
+}
+
+// AxisBinding maps inputs to a value in the positive and negative
+// directions. When inputs of both directions are active, they cancel out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AxisBinding {
+    pub positive: Vec<InputBinding>,
+    pub negative: Vec<InputBinding>,
+}
+
+impl AxisBinding {
+    pub fn new(positive: InputBinding, negative: InputBinding) -> Self {
+        Self {
+            positive: vec![positive],
+            negative: vec![negative],
+        }
+    }
+}
+
+// InputMap associates names to bindings so that games can query actions
+// ("jump") and axes ("move_x") instead of hardcoding keys.
+//
+// Maps are stored as RON so that they can be edited by hand.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputMap {
+    actions: BTreeMap<String, Vec<InputBinding>>,
+    axes: BTreeMap<String, AxisBinding>,
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let map = ron::from_str(&content)?;
+        Ok(map)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let content = ron::ser::to_string_pretty(self, PrettyConfig::default())?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn with_action(mut self, name: &str, binding: InputBinding) -> Self {
+        self.bind_action(name, binding);
+        self
+    }
+
+    pub fn with_axis(mut self, name: &str, positive: InputBinding, negative: InputBinding) -> Self {
+        self.bind_axis(name, AxisBinding::new(positive, negative));
+        self
+    }
+
+    // add a binding to an action, keeping the existing ones
+    pub fn bind_action(&mut self, name: &str, binding: InputBinding) {
+        let bindings = self.actions.entry(name.to_owned()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn unbind_action(&mut self, name: &str, binding: InputBinding) {
+        if let Some(bindings) = self.actions.get_mut(name) {
+            bindings.retain(|b| b != &binding);
+        }
+    }
+
+    // replace all the bindings of an action
+    pub fn rebind_action(&mut self, name: &str, bindings: Vec<InputBinding>) {
+        self.actions.insert(name.to_owned(), bindings);
+    }
+
+    pub fn action(&self, name: &str) -> &[InputBinding] {
+        self.actions.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn has_action(&self, name: &str) -> bool {
+        self.actions.contains_key(name)
+    }
+
+    // bind (or rebind) an axis
+    pub fn bind_axis(&mut self, name: &str, binding: AxisBinding) {
+        self.axes.insert(name.to_owned(), binding);
+    }
+
+    pub fn axis(&self, name: &str) -> Option<&AxisBinding> {
+        self.axes.get(name)
+    }
+
+    pub fn has_axis(&self, name: &str) -> bool {
+        self.axes.contains_key(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bind_action_ignores_duplicates() {
+        let mut map = InputMap::new();
+        map.bind_action("jump", InputBinding::Key(VirtualKeyCode::Space));
+        map.bind_action("jump", InputBinding::Key(VirtualKeyCode::Space));
+        map.bind_action("jump", InputBinding::MouseButton(MouseButton::Left));
+        assert_eq!(map.action("jump").len(), 2);
+
+        map.unbind_action("jump", InputBinding::Key(VirtualKeyCode::Space));
+        assert_eq!(
+            map.action("jump"),
+            &[InputBinding::MouseButton(MouseButton::Left)]
+        );
+        assert!(map.action("unknown").is_empty());
+    }
+
+    #[test]
+    fn roundtrip() {
+        let map = InputMap::new()
+            .with_action("jump", InputBinding::Key(VirtualKeyCode::Space))
+            .with_axis(
+                "move_x",
+                InputBinding::Key(VirtualKeyCode::D),
+                InputBinding::Key(VirtualKeyCode::A),
+            );
+
+        // one file per process so that concurrent test runs don't collide
+        let path = std::env::temp_dir().join(format!(
+            "vulkan-engine-input-map-roundtrip-{}.ron",
+            std::process::id()
+        ));
+        let saved = map.save(&path);
+        let loaded = InputMap::load(&path);
+        fs::remove_file(&path).ok();
+
+        saved.expect("save input map");
+        assert_eq!(map, loaded.expect("load input map"));
+    }
+}
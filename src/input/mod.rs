@@ -0,0 +1,15 @@
+mod button;
+mod gamepad;
+mod mapping;
+mod shortcut;
+mod system;
+mod text;
+mod touch;
+
+pub use gilrs::{Axis as GamepadAxis, Button as GamepadButton};
+
+pub use self::gamepad::{GamepadBackend, GamepadEvent, GamepadId};
+pub use self::mapping::{AxisBinding, InputBinding, InputMap};
+pub use self::shortcut::{KeyChord, KeySequence, Modifier};
+pub use self::system::{FileDropEvent, InputSystem};
+pub use self::touch::Gesture;
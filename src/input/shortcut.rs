@@ -0,0 +1,394 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use winit::event::{ModifiersState, VirtualKeyCode};
+
+// Modifier is either a generic modifier (Ctrl matches both the left and the
+// right control keys) or a specific side.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Modifier {
+    Shift,
+    Ctrl,
+    Alt,
+    Logo,
+    LShift,
+    RShift,
+    LCtrl,
+    RCtrl,
+    LAlt,
+    RAlt,
+    LLogo,
+    RLogo,
+}
+
+impl Modifier {
+    fn group(self) -> ModifiersState {
+        match self {
+            Modifier::Shift | Modifier::LShift | Modifier::RShift => ModifiersState::SHIFT,
+            Modifier::Ctrl | Modifier::LCtrl | Modifier::RCtrl => ModifiersState::CTRL,
+            Modifier::Alt | Modifier::LAlt | Modifier::RAlt => ModifiersState::ALT,
+            Modifier::Logo | Modifier::LLogo | Modifier::RLogo => ModifiersState::LOGO,
+        }
+    }
+
+    fn key(self) -> Option<VirtualKeyCode> {
+        match self {
+            Modifier::LShift => Some(VirtualKeyCode::LShift),
+            Modifier::RShift => Some(VirtualKeyCode::RShift),
+            Modifier::LCtrl => Some(VirtualKeyCode::LControl),
+            Modifier::RCtrl => Some(VirtualKeyCode::RControl),
+            Modifier::LAlt => Some(VirtualKeyCode::LAlt),
+            Modifier::RAlt => Some(VirtualKeyCode::RAlt),
+            Modifier::LLogo => Some(VirtualKeyCode::LWin),
+            Modifier::RLogo => Some(VirtualKeyCode::RWin),
+            _ => None,
+        }
+    }
+}
+
+fn is_modifier_key(key: VirtualKeyCode) -> bool {
+    matches!(
+        key,
+        VirtualKeyCode::LShift
+            | VirtualKeyCode::RShift
+            | VirtualKeyCode::LControl
+            | VirtualKeyCode::RControl
+            | VirtualKeyCode::LAlt
+            | VirtualKeyCode::RAlt
+            | VirtualKeyCode::LWin
+            | VirtualKeyCode::RWin
+    )
+}
+
+// KeyChord is a key pressed while holding a set of modifiers, e.g. Ctrl+S.
+//
+// Modifiers must match exactly: Ctrl+S is not triggered by Ctrl+Shift+S.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChord {
+    key: VirtualKeyCode,
+    modifiers: Vec<Modifier>,
+}
+
+impl KeyChord {
+    pub fn new(key: VirtualKeyCode) -> Self {
+        Self {
+            key,
+            modifiers: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, modifier: Modifier) -> Self {
+        self.modifiers.push(modifier);
+        self
+    }
+
+    fn matches(
+        &self,
+        key: VirtualKeyCode,
+        modifiers: ModifiersState,
+        is_held: &dyn Fn(VirtualKeyCode) -> bool,
+    ) -> bool {
+        if key != self.key {
+            return false;
+        }
+        let groups = self
+            .modifiers
+            .iter()
+            .fold(ModifiersState::empty(), |acc, m| acc | m.group());
+        let held = self.modifiers.iter().all(|m| match m.key() {
+            Some(key) => is_held(key),
+            None => modifiers.contains(m.group()),
+        });
+        held && modifiers == groups
+    }
+}
+
+impl From<VirtualKeyCode> for KeyChord {
+    fn from(key: VirtualKeyCode) -> Self {
+        KeyChord::new(key)
+    }
+}
+
+// KeySequence is a list of chords that must be pressed in order, each one
+// within timeout of the previous one (cheat codes, fighting game combos).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySequence {
+    steps: Vec<KeyChord>,
+    timeout: Duration,
+}
+
+impl KeySequence {
+    pub fn new<K: Into<KeyChord>>(steps: Vec<K>, timeout: Duration) -> Self {
+        Self {
+            steps: steps.into_iter().map(Into::into).collect(),
+            timeout,
+        }
+    }
+}
+
+struct SequenceState {
+    sequence: KeySequence,
+    // number of steps reached by each attempt in progress. A key may continue
+    // an attempt and start another one (Up Up Up Down for Up Up Down), and
+    // the attempts are tracked with the same matching as the steps so that a
+    // generic Ctrl step and a LCtrl step agree on which keys they accept.
+    attempts: Vec<usize>,
+    last_step: Option<Instant>,
+}
+
+impl SequenceState {
+    fn new(sequence: KeySequence) -> Self {
+        Self {
+            sequence,
+            attempts: Vec::new(),
+            last_step: None,
+        }
+    }
+
+    // returns true when the last step of the sequence has been reached
+    fn advance(
+        &mut self,
+        key: VirtualKeyCode,
+        modifiers: ModifiersState,
+        is_held: &dyn Fn(VirtualKeyCode) -> bool,
+        now: Instant,
+    ) -> bool {
+        if let Some(last_step) = self.last_step {
+            if now.duration_since(last_step) > self.sequence.timeout {
+                self.attempts.clear();
+            }
+        }
+        self.last_step = Some(now);
+
+        // every key may also start a new attempt
+        let steps = &self.sequence.steps;
+        self.attempts = self
+            .attempts
+            .iter()
+            .copied()
+            .chain(std::iter::once(0))
+            .filter(|&reached| steps[reached].matches(key, modifiers, is_held))
+            .map(|reached| reached + 1)
+            .collect();
+
+        if self.attempts.contains(&steps.len()) {
+            self.attempts.clear();
+            return true;
+        }
+        false
+    }
+}
+
+// Shortcuts detects registered chords and sequences. Triggered shortcuts can
+// be queried by name until the end of the tick.
+pub struct Shortcuts {
+    modifiers: ModifiersState,
+    chords: HashMap<String, KeyChord>,
+    sequences: HashMap<String, SequenceState>,
+    triggered: HashSet<String>,
+}
+
+impl Shortcuts {
+    pub fn new() -> Self {
+        Self {
+            modifiers: ModifiersState::empty(),
+            chords: HashMap::new(),
+            sequences: HashMap::new(),
+            triggered: HashSet::new(),
+        }
+    }
+
+    pub fn register_chord(&mut self, name: &str, chord: KeyChord) {
+        self.chords.insert(name.to_owned(), chord);
+    }
+
+    pub fn register_sequence(&mut self, name: &str, sequence: KeySequence) {
+        if sequence.steps.is_empty() {
+            return;
+        }
+        self.sequences
+            .insert(name.to_owned(), SequenceState::new(sequence));
+    }
+
+    pub fn unregister(&mut self, name: &str) {
+        self.chords.remove(name);
+        self.sequences.remove(name);
+    }
+
+    pub fn on_modifiers_changed(&mut self, modifiers: ModifiersState) {
+        self.modifiers = modifiers;
+    }
+
+    // must be called for every new key press (not for key repeats), in the
+    // order they are received
+    pub fn on_key_pressed(
+        &mut self,
+        key: VirtualKeyCode,
+        is_held: &dyn Fn(VirtualKeyCode) -> bool,
+        now: Instant,
+    ) {
+        for (name, chord) in &self.chords {
+            if chord.matches(key, self.modifiers, is_held) {
+                self.triggered.insert(name.clone());
+            }
+        }
+
+        // pressing a modifier is part of a chord, not a step of its own
+        if is_modifier_key(key) {
+            return;
+        }
+        for (name, state) in &mut self.sequences {
+            if state.advance(key, self.modifiers, is_held, now) {
+                self.triggered.insert(name.clone());
+            }
+        }
+    }
+
+    pub fn end_tick(&mut self) {
+        self.triggered.clear();
+    }
+
+    pub fn reset(&mut self) {
+        self.modifiers = ModifiersState::empty();
+        for state in self.sequences.values_mut() {
+            state.attempts.clear();
+            state.last_step = None;
+        }
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    pub fn is_triggered(&self, name: &str) -> bool {
+        self.triggered.contains(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn none(_: VirtualKeyCode) -> bool {
+        false
+    }
+
+    #[test]
+    fn chord_modifiers_match_exactly() {
+        let mut shortcuts = Shortcuts::new();
+        shortcuts.register_chord(
+            "save",
+            KeyChord::new(VirtualKeyCode::S).with(Modifier::Ctrl),
+        );
+        let now = Instant::now();
+
+        shortcuts.on_key_pressed(VirtualKeyCode::S, &none, now);
+        assert!(!shortcuts.is_triggered("save"));
+
+        shortcuts.on_modifiers_changed(ModifiersState::CTRL | ModifiersState::SHIFT);
+        shortcuts.on_key_pressed(VirtualKeyCode::S, &none, now);
+        assert!(!shortcuts.is_triggered("save"));
+
+        shortcuts.on_modifiers_changed(ModifiersState::CTRL);
+        shortcuts.on_key_pressed(VirtualKeyCode::S, &none, now);
+        assert!(shortcuts.is_triggered("save"));
+
+        shortcuts.end_tick();
+        assert!(!shortcuts.is_triggered("save"));
+    }
+
+    #[test]
+    fn chord_modifier_side() {
+        let mut shortcuts = Shortcuts::new();
+        shortcuts.register_chord(
+            "fullscreen",
+            KeyChord::new(VirtualKeyCode::Return).with(Modifier::RAlt),
+        );
+        shortcuts.on_modifiers_changed(ModifiersState::ALT);
+        let now = Instant::now();
+
+        let left_alt = |key| key == VirtualKeyCode::LAlt;
+        shortcuts.on_key_pressed(VirtualKeyCode::Return, &left_alt, now);
+        assert!(!shortcuts.is_triggered("fullscreen"));
+
+        let right_alt = |key| key == VirtualKeyCode::RAlt;
+        shortcuts.on_key_pressed(VirtualKeyCode::Return, &right_alt, now);
+        assert!(shortcuts.is_triggered("fullscreen"));
+    }
+
+    #[test]
+    fn sequence_in_order_and_in_time() {
+        use VirtualKeyCode::{Down, Right, A};
+
+        let mut shortcuts = Shortcuts::new();
+        let timeout = Duration::from_millis(200);
+        shortcuts.register_sequence("hadoken", KeySequence::new(vec![Down, Right, A], timeout));
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        // too slow between the last two steps
+        shortcuts.on_key_pressed(Down, &none, at(0));
+        shortcuts.on_key_pressed(Right, &none, at(100));
+        shortcuts.on_key_pressed(A, &none, at(400));
+        assert!(!shortcuts.is_triggered("hadoken"));
+
+        // a wrong key restarts the sequence
+        shortcuts.on_key_pressed(Down, &none, at(1000));
+        shortcuts.on_key_pressed(Down, &none, at(1050));
+        shortcuts.on_key_pressed(Right, &none, at(1100));
+        shortcuts.on_key_pressed(A, &none, at(1200));
+        assert!(shortcuts.is_triggered("hadoken"));
+    }
+
+    #[test]
+    fn sequence_with_repeated_steps() {
+        use VirtualKeyCode::{Down, Up, A, B};
+
+        let mut shortcuts = Shortcuts::new();
+        let timeout = Duration::from_secs(1);
+        shortcuts.register_sequence(
+            "konami",
+            KeySequence::new(vec![Up, Up, Down, Down, A, B], timeout),
+        );
+        let now = Instant::now();
+
+        // the extra Up is the start of the sequence, not a mistake
+        for key in [Up, Up, Up, Down, Down, A, B] {
+            shortcuts.on_key_pressed(key, &none, now);
+        }
+        assert!(shortcuts.is_triggered("konami"));
+    }
+
+    #[test]
+    fn sequence_with_overlapping_modifier_sides() {
+        use VirtualKeyCode::{LControl, K, Y};
+
+        let mut shortcuts = Shortcuts::new();
+        let timeout = Duration::from_secs(1);
+        shortcuts.register_sequence(
+            "chord",
+            KeySequence::new(
+                vec![
+                    KeyChord::new(K).with(Modifier::Ctrl),
+                    KeyChord::new(K).with(Modifier::LCtrl),
+                    KeyChord::new(Y),
+                ],
+                timeout,
+            ),
+        );
+        let now = Instant::now();
+        let left_ctrl = |key| key == LControl;
+
+        // the third LCtrl+K is the second step of an attempt started by the
+        // second one, which also matches the generic first step
+        shortcuts.on_modifiers_changed(ModifiersState::CTRL);
+        for _ in 0..3 {
+            shortcuts.on_key_pressed(K, &left_ctrl, now);
+        }
+        shortcuts.on_modifiers_changed(ModifiersState::empty());
+        shortcuts.on_key_pressed(Y, &none, now);
+        assert!(shortcuts.is_triggered("chord"));
+    }
+}
@@ -0,0 +1,714 @@
+use std::{collections::HashSet, path::PathBuf, time::Instant};
+
+use cgmath::{Vector2, Zero};
+use gilrs::{Axis, Button};
+use winit::{
+    dpi::PhysicalPosition,
+    event::{
+        DeviceEvent, ElementState, Event, KeyboardInput, ModifiersState, MouseButton,
+        MouseScrollDelta, Touch, VirtualKeyCode, WindowEvent,
+    },
+};
+
+use super::{
+    button::ButtonState,
+    gamepad::{GamepadEvent, GamepadId, Gamepads},
+    mapping::{InputBinding, InputMap},
+    shortcut::{KeyChord, KeySequence, Shortcuts},
+    text::TextInput,
+    touch::{Gesture, TouchInput},
+};
+
+// analog bindings are considered pressed past this value
+const ANALOG_PRESS_THRESHOLD: f32 = 0.5;
+
+// FileDropEvent is forwarded to the application when a file is dragged over
+// or dropped on the window. The position is the cursor position in world
+// space.
+#[derive(Debug, Clone)]
+pub enum FileDropEvent {
+    Hovered {
+        path: PathBuf,
+        position: Vector2<f32>,
+    },
+    Dropped {
+        path: PathBuf,
+        position: Vector2<f32>,
+    },
+    HoverCancelled,
+}
+
+// approximate height of a line of text, used to express pixel deltas as lines
+const SCROLL_PIXELS_PER_LINE: f32 = 20.0;
+
+// ScrollState accumulates scroll deltas since the last tick. Mouse wheels
+// report lines (notches) while touchpads report pixels, so both are kept
+// separate.
+struct ScrollState {
+    lines: Vector2<f32>,
+    pixels: Vector2<f32>,
+}
+
+impl ScrollState {
+    fn new() -> Self {
+        Self {
+            lines: Vector2::zero(),
+            pixels: Vector2::zero(),
+        }
+    }
+
+    fn on_delta(&mut self, delta: MouseScrollDelta) {
+        match delta {
+            MouseScrollDelta::LineDelta(x, y) => {
+                self.lines += Vector2::new(x, y);
+            }
+            MouseScrollDelta::PixelDelta(PhysicalPosition { x, y }) => {
+                self.pixels += Vector2::new(x as f32, y as f32);
+            }
+        }
+    }
+
+    // total scroll expressed in lines
+    fn combined(&self) -> Vector2<f32> {
+        self.lines + self.pixels / SCROLL_PIXELS_PER_LINE
+    }
+}
+
+// Consumed holds the inputs claimed during the current tick by a higher
+// priority reader (a menu, a console). Readers coming after it see these
+// inputs as released.
+#[derive(Default)]
+struct Consumed {
+    keyboard: bool,
+    keys: HashSet<VirtualKeyCode>,
+    mouse_buttons: bool,
+    buttons: HashSet<MouseButton>,
+    scroll: bool,
+    // cursor position and raw mouse motion
+    cursor: bool,
+    touch: bool,
+    gamepad: bool,
+    shortcuts: bool,
+    text: bool,
+}
+
+impl Consumed {
+    fn is_key_consumed(&self, key: VirtualKeyCode) -> bool {
+        self.keyboard || self.keys.contains(&key)
+    }
+
+    fn is_mouse_button_consumed(&self, button: MouseButton) -> bool {
+        self.mouse_buttons || self.buttons.contains(&button)
+    }
+}
+
+pub struct InputSystem {
+    keyboard: ButtonState<VirtualKeyCode>,
+    mouse_buttons: ButtonState<MouseButton>,
+    scroll_state: ScrollState,
+    cursor_position: PhysicalPosition<f64>,
+    cursor_in_window: bool,
+    mouse_motion: Vector2<f32>,
+    gamepads: Gamepads,
+    text: TextInput,
+    shortcuts: Shortcuts,
+    touch: TouchInput,
+    consumed: Consumed,
+    map: InputMap,
+}
+
+impl InputSystem {
+    pub fn new() -> Self {
+        Self {
+            keyboard: ButtonState::new(),
+            mouse_buttons: ButtonState::new(),
+            scroll_state: ScrollState::new(),
+            cursor_position: PhysicalPosition::new(0.0, 0.0),
+            cursor_in_window: false,
+            mouse_motion: Vector2::zero(),
+            gamepads: Gamepads::new(),
+            text: TextInput::new(),
+            shortcuts: Shortcuts::new(),
+            touch: TouchInput::new(),
+            consumed: Consumed::default(),
+            map: InputMap::new(),
+        }
+    }
+
+    pub fn map(&self) -> &InputMap {
+        &self.map
+    }
+
+    pub fn map_mut(&mut self) -> &mut InputMap {
+        &mut self.map
+    }
+
+    pub fn set_map(&mut self, map: InputMap) {
+        self.map = map;
+    }
+
+    // must be called once every tick, after the tick has been processed
+    pub fn end_tick(&mut self) {
+        self.keyboard.end_tick();
+        self.mouse_buttons.end_tick();
+        self.gamepads.end_tick();
+        self.text.end_tick();
+        self.shortcuts.end_tick();
+        self.touch.end_tick(Instant::now());
+        self.consumed = Consumed::default();
+        self.scroll_state = ScrollState::new();
+        self.mouse_motion = Vector2::zero();
+    }
+
+    pub fn consume_key(&mut self, key: VirtualKeyCode) {
+        self.consumed.keys.insert(key);
+    }
+    pub fn consume_keyboard(&mut self) {
+        self.consumed.keyboard = true;
+    }
+    pub fn consume_mouse_button(&mut self, button: MouseButton) {
+        self.consumed.buttons.insert(button);
+    }
+    pub fn consume_scroll(&mut self) {
+        self.consumed.scroll = true;
+    }
+    pub fn consume_touch(&mut self) {
+        self.consumed.touch = true;
+    }
+    pub fn consume_cursor(&mut self) {
+        self.consumed.cursor = true;
+    }
+    pub fn consume_gamepad(&mut self) {
+        self.consumed.gamepad = true;
+    }
+    pub fn consume_shortcuts(&mut self) {
+        self.consumed.shortcuts = true;
+    }
+    pub fn consume_text(&mut self) {
+        self.consumed.text = true;
+    }
+    // consume the mouse buttons, the scroll and the cursor
+    pub fn consume_mouse(&mut self) {
+        self.consumed.mouse_buttons = true;
+        self.consumed.scroll = true;
+        self.consumed.cursor = true;
+    }
+    pub fn consume_all(&mut self) {
+        self.consume_keyboard();
+        self.consume_mouse();
+        self.consume_touch();
+        self.consume_gamepad();
+        self.consume_shortcuts();
+        self.consume_text();
+    }
+
+    pub fn register_chord(&mut self, name: &str, chord: KeyChord) {
+        self.shortcuts.register_chord(name, chord)
+    }
+
+    pub fn register_sequence(&mut self, name: &str, sequence: KeySequence) {
+        self.shortcuts.register_sequence(name, sequence)
+    }
+
+    pub fn unregister_shortcut(&mut self, name: &str) {
+        self.shortcuts.unregister(name)
+    }
+
+    pub fn is_shortcut_triggered(&self, name: &str) -> bool {
+        !self.consumed.shortcuts && self.shortcuts.is_triggered(name)
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.shortcuts.modifiers()
+    }
+
+    pub fn text(&self) -> &TextInput {
+        &self.text
+    }
+
+    pub fn text_mut(&mut self) -> &mut TextInput {
+        &mut self.text
+    }
+
+    // characters typed since the last tick, unless the text has been consumed
+    pub fn text_input(&self) -> &str {
+        if self.consumed.text {
+            return "";
+        }
+        self.text.text()
+    }
+
+    pub fn text_edit_keys(&self) -> &[VirtualKeyCode] {
+        if self.consumed.text {
+            return &[];
+        }
+        self.text.edit_keys()
+    }
+
+    // fingers touching the window, unless the touch input has been consumed
+    pub fn fingers(&self) -> impl Iterator<Item = (u64, Vector2<f32>)> + '_ {
+        let consumed = self.consumed.touch;
+        self.touch.fingers().filter(move |_| !consumed)
+    }
+
+    // gestures of the tick, unless the touch input has been consumed
+    pub fn gestures(&self) -> &[Gesture] {
+        if self.consumed.touch {
+            return &[];
+        }
+        self.touch.gestures()
+    }
+
+    pub fn on_gamepad_event(&mut self, event: GamepadEvent) {
+        self.gamepads.on_event(event);
+    }
+
+    pub fn gamepads(&self) -> &Gamepads {
+        &self.gamepads
+    }
+
+    pub fn gamepads_mut(&mut self) -> &mut Gamepads {
+        &mut self.gamepads
+    }
+
+    // consumed gamepads are reported as released and centered, connections
+    // and disconnections are still reported
+    pub fn gamepad_events(&self) -> impl Iterator<Item = GamepadEvent> + '_ {
+        let consumed = self.consumed.gamepad;
+        self.gamepads.events().iter().copied().filter(move |event| {
+            !consumed
+                || matches!(
+                    event,
+                    GamepadEvent::Connected(_) | GamepadEvent::Disconnected(_)
+                )
+        })
+    }
+    pub fn is_gamepad_button_pressed(&self, id: GamepadId, button: Button) -> bool {
+        !self.consumed.gamepad && self.gamepads.is_button_pressed(id, button)
+    }
+    pub fn is_gamepad_button_just_pressed(&self, id: GamepadId, button: Button) -> bool {
+        !self.consumed.gamepad && self.gamepads.is_button_just_pressed(id, button)
+    }
+    pub fn is_gamepad_button_just_released(&self, id: GamepadId, button: Button) -> bool {
+        !self.consumed.gamepad && self.gamepads.is_button_just_released(id, button)
+    }
+    pub fn gamepad_button_value(&self, id: GamepadId, button: Button) -> f32 {
+        if self.consumed.gamepad {
+            return 0.0;
+        }
+        self.gamepads.button_value(id, button)
+    }
+    pub fn gamepad_axis(&self, id: GamepadId, axis: Axis) -> f32 {
+        if self.consumed.gamepad {
+            return 0.0;
+        }
+        self.gamepads.axis(id, axis)
+    }
+
+    pub fn on_event(&mut self, event: &Event<()>) {
+        #[allow(clippy::single_match)]
+        #[allow(clippy::collapsible_match)]
+        match event {
+            Event::WindowEvent { ref event, .. } => match *event {
+                // handle keys
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state,
+                            virtual_keycode,
+                            ..
+                        },
+                    ..
+                } => {
+                    if let Some(keycode) = virtual_keycode {
+                        let repeat = self.keyboard.is_pressed(keycode);
+                        self.keyboard.on_input(keycode, state);
+                        if state == ElementState::Pressed {
+                            self.text.on_key_pressed(keycode);
+                        }
+                        if state == ElementState::Pressed && !repeat {
+                            let keyboard = &self.keyboard;
+                            self.shortcuts.on_key_pressed(
+                                keycode,
+                                &|k| keyboard.is_pressed(k),
+                                Instant::now(),
+                            );
+                        }
+                    }
+                }
+                // handle modifiers
+                WindowEvent::ModifiersChanged(modifiers) => {
+                    self.shortcuts.on_modifiers_changed(modifiers);
+                }
+                // handle text
+                WindowEvent::ReceivedCharacter(c) => {
+                    self.text.on_character(c);
+                }
+                // handle mouse buttons
+                WindowEvent::MouseInput { state, button, .. } => {
+                    self.mouse_buttons.on_input(button, state);
+                }
+                // handle cursor
+                WindowEvent::CursorMoved { position, .. } => {
+                    self.cursor_position = position;
+                }
+                WindowEvent::CursorEntered { .. } => {
+                    self.cursor_in_window = true;
+                }
+                WindowEvent::CursorLeft { .. } => {
+                    self.cursor_in_window = false;
+                }
+                // handle mouse scroll
+                WindowEvent::MouseWheel { delta, .. } => {
+                    self.scroll_state.on_delta(delta);
+                }
+                // handle touch
+                WindowEvent::Touch(Touch {
+                    id,
+                    phase,
+                    location,
+                    ..
+                }) => {
+                    let position = Vector2::new(location.x as f32, location.y as f32);
+                    self.touch.on_touch(id, phase, position, Instant::now());
+                }
+                // reset state when losing focus
+                WindowEvent::Focused(false) => {
+                    self.keyboard.release_all();
+                    self.mouse_buttons.release_all();
+                    self.shortcuts.reset();
+                    self.touch.reset();
+                    self.scroll_state = ScrollState::new();
+                    self.mouse_motion = Vector2::zero();
+                }
+                _ => {}
+            },
+            // raw mouse motion, not limited by the window borders nor affected
+            // by the cursor acceleration
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta: (x, y) },
+                ..
+            } => {
+                self.mouse_motion += Vector2::new(*x as f32, *y as f32);
+            }
+            _ => {}
+        }
+    }
+
+    // consumed keys and buttons are reported as released, without transitions
+    pub fn is_key_pressed(&self, key: VirtualKeyCode) -> bool {
+        !self.consumed.is_key_consumed(key) && self.keyboard.is_pressed(key)
+    }
+    pub fn is_key_released(&self, key: VirtualKeyCode) -> bool {
+        self.consumed.is_key_consumed(key) || self.keyboard.is_released(key)
+    }
+    pub fn is_key_just_pressed(&self, key: VirtualKeyCode) -> bool {
+        !self.consumed.is_key_consumed(key) && self.keyboard.is_just_pressed(key)
+    }
+    pub fn is_key_just_released(&self, key: VirtualKeyCode) -> bool {
+        !self.consumed.is_key_consumed(key) && self.keyboard.is_just_released(key)
+    }
+
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        !self.consumed.is_mouse_button_consumed(button) && self.mouse_buttons.is_pressed(button)
+    }
+    pub fn is_mouse_button_released(&self, button: MouseButton) -> bool {
+        self.consumed.is_mouse_button_consumed(button) || self.mouse_buttons.is_released(button)
+    }
+    pub fn is_mouse_button_just_pressed(&self, button: MouseButton) -> bool {
+        !self.consumed.is_mouse_button_consumed(button)
+            && self.mouse_buttons.is_just_pressed(button)
+    }
+    pub fn is_mouse_button_just_released(&self, button: MouseButton) -> bool {
+        !self.consumed.is_mouse_button_consumed(button)
+            && self.mouse_buttons.is_just_released(button)
+    }
+
+    pub fn is_action_pressed(&self, name: &str) -> bool {
+        self.map
+            .action(name)
+            .iter()
+            .any(|b| self.is_binding_pressed(b))
+    }
+    pub fn is_action_just_pressed(&self, name: &str) -> bool {
+        self.map
+            .action(name)
+            .iter()
+            .any(|b| self.is_binding_just_pressed(b))
+    }
+    // an action is released when none of its bindings are held anymore
+    pub fn is_action_just_released(&self, name: &str) -> bool {
+        let bindings = self.map.action(name);
+        bindings.iter().any(|b| self.is_binding_just_released(b))
+            && !bindings.iter().any(|b| self.is_binding_pressed(b))
+    }
+
+    // value of an axis, usually between -1.0 and 1.0 except for scroll
+    // bindings which report the number of lines scrolled
+    pub fn axis(&self, name: &str) -> f32 {
+        let axis = match self.map.axis(name) {
+            Some(axis) => axis,
+            None => return 0.0,
+        };
+        let strength = |bindings: &[InputBinding]| {
+            bindings
+                .iter()
+                .map(|b| self.binding_value(b))
+                .fold(0.0, f32::max)
+        };
+        strength(&axis.positive) - strength(&axis.negative)
+    }
+
+    fn binding_value(&self, binding: &InputBinding) -> f32 {
+        let scroll = self.scroll_combined();
+        match *binding {
+            InputBinding::ScrollUp => scroll.y.max(0.0),
+            InputBinding::ScrollDown => (-scroll.y).max(0.0),
+            InputBinding::ScrollRight => scroll.x.max(0.0),
+            InputBinding::ScrollLeft => (-scroll.x).max(0.0),
+            InputBinding::GamepadButton(_)
+            | InputBinding::GamepadAxisPositive(_)
+            | InputBinding::GamepadAxisNegative(_)
+                if self.consumed.gamepad =>
+            {
+                0.0
+            }
+            InputBinding::GamepadButton(button) => self.gamepads.any_button_value(button),
+            InputBinding::GamepadAxisPositive(axis) => self.gamepads.any_axis(axis).max(0.0),
+            InputBinding::GamepadAxisNegative(axis) => (-self.gamepads.any_axis(axis)).max(0.0),
+            _ if self.is_binding_pressed(binding) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    fn is_binding_pressed(&self, binding: &InputBinding) -> bool {
+        match *binding {
+            InputBinding::Key(key) => self.is_key_pressed(key),
+            InputBinding::MouseButton(button) => self.is_mouse_button_pressed(button),
+            InputBinding::GamepadButton(button) => {
+                !self.consumed.gamepad && self.gamepads.any_button_pressed(button)
+            }
+            InputBinding::GamepadAxisPositive(_) | InputBinding::GamepadAxisNegative(_) => {
+                self.binding_value(binding) > ANALOG_PRESS_THRESHOLD
+            }
+            _ => self.binding_value(binding) > 0.0,
+        }
+    }
+
+    // scrolling has no duration, so it is pressed only for the tick it happens
+    fn is_binding_just_pressed(&self, binding: &InputBinding) -> bool {
+        match *binding {
+            InputBinding::Key(key) => self.is_key_just_pressed(key),
+            InputBinding::MouseButton(button) => self.is_mouse_button_just_pressed(button),
+            InputBinding::GamepadButton(button) => {
+                !self.consumed.gamepad && self.gamepads.any_button_just_pressed(button)
+            }
+            InputBinding::GamepadAxisPositive(_) | InputBinding::GamepadAxisNegative(_) => false,
+            _ => self.is_binding_pressed(binding),
+        }
+    }
+
+    fn is_binding_just_released(&self, binding: &InputBinding) -> bool {
+        match *binding {
+            InputBinding::Key(key) => self.is_key_just_released(key),
+            InputBinding::MouseButton(button) => self.is_mouse_button_just_released(button),
+            InputBinding::GamepadButton(button) => {
+                !self.consumed.gamepad && self.gamepads.any_button_just_released(button)
+            }
+            _ => false,
+        }
+    }
+
+    // last known cursor position, in physical pixels
+    pub fn cursor_position(&self) -> PhysicalPosition<f64> {
+        self.cursor_position
+    }
+
+    // cursor position for hovering and scrolling, none when the cursor is
+    // outside of the window or consumed
+    pub fn cursor(&self) -> Option<PhysicalPosition<f64>> {
+        self.is_cursor_in_window().then_some(self.cursor_position)
+    }
+
+    // a consumed cursor is seen as outside of the window, so that nothing
+    // hovers or scrolls with it
+    pub fn is_cursor_in_window(&self) -> bool {
+        !self.consumed.cursor && self.cursor_in_window
+    }
+
+    // raw mouse motion accumulated since the last tick
+    pub fn mouse_motion(&self) -> Vector2<f32> {
+        if self.consumed.cursor {
+            return Vector2::zero();
+        }
+        self.mouse_motion
+    }
+
+    // scroll accumulated since the last tick, in lines
+    pub fn mouse_scroll_lines(&self) -> Vector2<f32> {
+        if self.consumed.scroll {
+            return Vector2::zero();
+        }
+        self.scroll_state.lines
+    }
+    // scroll accumulated since the last tick, in physical pixels
+    pub fn mouse_scroll_pixels(&self) -> Vector2<f32> {
+        if self.consumed.scroll {
+            return Vector2::zero();
+        }
+        self.scroll_state.pixels
+    }
+
+    fn scroll_combined(&self) -> Vector2<f32> {
+        if self.consumed.scroll {
+            return Vector2::zero();
+        }
+        self.scroll_state.combined()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::event::TouchPhase;
+
+    use super::*;
+
+    // whether an input is still seen, and a way to consume it
+    type Probe = fn(&InputSystem) -> bool;
+    type Consume = fn(&mut InputSystem);
+
+    #[test]
+    fn scroll_accumulates() {
+        let mut scroll = ScrollState::new();
+        scroll.on_delta(MouseScrollDelta::LineDelta(0.0, 1.0));
+        scroll.on_delta(MouseScrollDelta::LineDelta(0.0, 1.0));
+        scroll.on_delta(MouseScrollDelta::LineDelta(-0.5, 0.0));
+        scroll.on_delta(MouseScrollDelta::PixelDelta(PhysicalPosition::new(
+            0.0, 40.0,
+        )));
+
+        assert_eq!(scroll.lines, Vector2::new(-0.5, 2.0));
+        assert_eq!(scroll.pixels, Vector2::new(0.0, 40.0));
+        assert_eq!(scroll.combined(), Vector2::new(-0.5, 4.0));
+    }
+
+    #[test]
+    fn consumed_inputs_are_released() {
+        let mut input = InputSystem::new();
+        input
+            .keyboard
+            .on_input(VirtualKeyCode::W, ElementState::Pressed);
+        input
+            .keyboard
+            .on_input(VirtualKeyCode::A, ElementState::Pressed);
+        input
+            .scroll_state
+            .on_delta(MouseScrollDelta::LineDelta(0.0, 1.0));
+        input.on_gamepad_event(GamepadEvent::ButtonPressed(GamepadId(0), Button::South));
+        input.text.set_enabled(true);
+        input.text.on_character('w');
+        input.set_map(
+            InputMap::new()
+                .with_action("up", InputBinding::Key(VirtualKeyCode::W))
+                .with_action("zoom", InputBinding::ScrollUp)
+                .with_action("jump", InputBinding::GamepadButton(Button::South)),
+        );
+        assert!(input.is_action_just_pressed("jump"));
+
+        input.consume_key(VirtualKeyCode::W);
+        input.consume_scroll();
+        input.consume_gamepad();
+        input.consume_text();
+        assert!(!input.is_key_pressed(VirtualKeyCode::W));
+        assert!(!input.is_key_just_pressed(VirtualKeyCode::W));
+        assert!(input.is_key_released(VirtualKeyCode::W));
+        assert!(input.is_key_pressed(VirtualKeyCode::A));
+        assert!(!input.is_action_pressed("up"));
+        assert!(!input.is_action_pressed("zoom"));
+        assert_eq!(input.mouse_scroll_lines(), Vector2::zero());
+        assert!(!input.is_action_pressed("jump"));
+        assert!(!input.is_action_just_pressed("jump"));
+        assert!(!input.is_gamepad_button_pressed(GamepadId(0), Button::South));
+        assert_eq!(input.gamepad_events().count(), 0);
+        assert_eq!(input.text_input(), "");
+
+        // consumption only lasts for the tick
+        input.end_tick();
+        assert!(input.is_key_pressed(VirtualKeyCode::W));
+        assert!(input.is_action_pressed("up"));
+        assert!(input.is_action_pressed("jump"));
+    }
+
+    #[test]
+    fn every_consume_path() {
+        let fresh = || {
+            let mut input = InputSystem::new();
+            input
+                .keyboard
+                .on_input(VirtualKeyCode::S, ElementState::Pressed);
+            input
+                .mouse_buttons
+                .on_input(MouseButton::Left, ElementState::Pressed);
+            input
+                .scroll_state
+                .on_delta(MouseScrollDelta::LineDelta(0.0, 1.0));
+            input.cursor_in_window = true;
+            input.mouse_motion = Vector2::new(3.0, 4.0);
+            input
+                .touch
+                .on_touch(0, TouchPhase::Started, Vector2::zero(), Instant::now());
+            input.on_gamepad_event(GamepadEvent::ButtonPressed(GamepadId(0), Button::South));
+            input.register_chord("save", KeyChord::new(VirtualKeyCode::S));
+            input
+                .shortcuts
+                .on_key_pressed(VirtualKeyCode::S, &|_| false, Instant::now());
+            input.text.set_enabled(true);
+            input.text.on_character('s');
+            input
+        };
+        let all: [(&str, Probe); 8] = [
+            ("keyboard", |i| i.is_key_pressed(VirtualKeyCode::S)),
+            ("mouse_button", |i| {
+                i.is_mouse_button_pressed(MouseButton::Left)
+            }),
+            ("scroll", |i| i.mouse_scroll_lines() != Vector2::zero()),
+            ("cursor", |i| {
+                i.cursor().is_some() || i.mouse_motion() != Vector2::zero()
+            }),
+            ("touch", |i| i.fingers().count() > 0),
+            ("gamepad", |i| {
+                i.is_gamepad_button_pressed(GamepadId(0), Button::South)
+            }),
+            ("shortcut", |i| i.is_shortcut_triggered("save")),
+            ("text", |i| !i.text_input().is_empty()),
+        ];
+
+        // each consume call blocks exactly the inputs it is named after
+        let cases: [(Consume, &[&str]); 10] = [
+            (|i| i.consume_key(VirtualKeyCode::S), &["keyboard"]),
+            (|i| i.consume_keyboard(), &["keyboard"]),
+            (
+                |i| i.consume_mouse_button(MouseButton::Left),
+                &["mouse_button"],
+            ),
+            (|i| i.consume_scroll(), &["scroll"]),
+            (|i| i.consume_cursor(), &["cursor"]),
+            (|i| i.consume_mouse(), &["mouse_button", "scroll", "cursor"]),
+            (|i| i.consume_touch(), &["touch"]),
+            (|i| i.consume_gamepad(), &["gamepad"]),
+            (|i| i.consume_shortcuts(), &["shortcut"]),
+            (|i| i.consume_text(), &["text"]),
+        ];
+        for (consume, blocked) in cases {
+            let mut input = fresh();
+            consume(&mut input);
+            for (name, seen) in all {
+                assert_eq!(seen(&input), !blocked.contains(&name), "{}", name);
+            }
+        }
+
+        let mut input = fresh();
+        input.consume_all();
+        assert!(all.iter().all(|(_, seen)| !seen(&input)));
+    }
+}
@@ -0,0 +1,111 @@
+use winit::event::VirtualKeyCode;
+
+// keys used to edit text, they are reported on press and on every key repeat
+const EDIT_KEYS: [VirtualKeyCode; 11] = [
+    VirtualKeyCode::Back,
+    VirtualKeyCode::Delete,
+    VirtualKeyCode::Left,
+    VirtualKeyCode::Right,
+    VirtualKeyCode::Up,
+    VirtualKeyCode::Down,
+    VirtualKeyCode::Home,
+    VirtualKeyCode::End,
+    VirtualKeyCode::Return,
+    VirtualKeyCode::NumpadEnter,
+    VirtualKeyCode::Tab,
+];
+
+// TextInput collects the characters typed since the last tick, including the
+// ones committed by an IME, as well as the editing keys.
+//
+// It is disabled by default so that games do not accumulate text they never
+// read.
+pub struct TextInput {
+    enabled: bool,
+    text: String,
+    edit_keys: Vec<VirtualKeyCode>,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            text: String::new(),
+            edit_keys: Vec::new(),
+        }
+    }
+
+    pub fn set_enabled(&mut self, b: bool) {
+        self.enabled = b;
+        if !b {
+            self.end_tick();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn on_character(&mut self, c: char) {
+        // control characters (backspace, return, ...) are reported as edit keys
+        if self.enabled && !c.is_control() {
+            self.text.push(c);
+        }
+    }
+
+    // must be called for every key press, including key repeats
+    pub fn on_key_pressed(&mut self, key: VirtualKeyCode) {
+        if self.enabled && EDIT_KEYS.contains(&key) {
+            self.edit_keys.push(key);
+        }
+    }
+
+    pub fn end_tick(&mut self) {
+        self.text.clear();
+        self.edit_keys.clear();
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn edit_keys(&self) -> &[VirtualKeyCode] {
+        &self.edit_keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_by_default() {
+        let mut text = TextInput::new();
+        text.on_character('a');
+        text.on_key_pressed(VirtualKeyCode::Back);
+        assert!(text.text().is_empty());
+        assert!(text.edit_keys().is_empty());
+    }
+
+    #[test]
+    fn collects_text_and_repeats() {
+        let mut text = TextInput::new();
+        text.set_enabled(true);
+        for c in ['h', 'é', '\u{8}', '\r', '日'] {
+            text.on_character(c);
+        }
+        text.on_key_pressed(VirtualKeyCode::Back);
+        text.on_key_pressed(VirtualKeyCode::Back);
+        text.on_key_pressed(VirtualKeyCode::A);
+
+        assert_eq!(text.text(), "hé日");
+        assert_eq!(
+            text.edit_keys(),
+            &[VirtualKeyCode::Back, VirtualKeyCode::Back]
+        );
+
+        text.end_tick();
+        assert!(text.text().is_empty());
+        assert!(text.edit_keys().is_empty());
+    }
+}
@@ -0,0 +1,318 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use cgmath::{InnerSpace, Vector2, Zero};
+use winit::event::TouchPhase;
+
+// distance a finger can travel, in physical pixels, before a touch stops
+// being a tap or a long press
+const TAP_SLOP: f32 = 10.0;
+// time a finger must be held still to trigger a long press
+const LONG_PRESS_DURATION: Duration = Duration::from_millis(500);
+
+// Gesture is recognized from the touches received during a tick. Positions
+// and deltas are expressed in physical pixels.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Gesture {
+    // a single finger quickly pressed and released without moving
+    Tap {
+        position: Vector2<f32>,
+    },
+    // a single finger held without moving, reported once
+    LongPress {
+        position: Vector2<f32>,
+    },
+    // one or more fingers moved, position is the center of the fingers
+    Pan {
+        position: Vector2<f32>,
+        delta: Vector2<f32>,
+    },
+    // two fingers moved, scale is the ratio between the new and the previous
+    // distance of the fingers (greater than 1.0 when spreading them)
+    Pinch {
+        center: Vector2<f32>,
+        scale: f32,
+    },
+}
+
+impl Gesture {
+    pub fn to_logical(self, scale_factor: f64) -> Self {
+        let f = scale_factor as f32;
+        match self {
+            Gesture::Tap { position } => Gesture::Tap {
+                position: position / f,
+            },
+            Gesture::LongPress { position } => Gesture::LongPress {
+                position: position / f,
+            },
+            Gesture::Pan { position, delta } => Gesture::Pan {
+                position: position / f,
+                delta: delta / f,
+            },
+            Gesture::Pinch { center, scale } => Gesture::Pinch {
+                center: center / f,
+                scale,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Finger {
+    start: Vector2<f32>,
+    position: Vector2<f32>,
+    start_time: Instant,
+}
+
+// TouchInput tracks the fingers touching the window and recognizes gestures.
+//
+// An interaction starts when the first finger touches the window and ends
+// when the last one is lifted; taps and long presses are only recognized for
+// single finger interactions.
+pub struct TouchInput {
+    fingers: BTreeMap<u64, Finger>,
+    gestures: Vec<Gesture>,
+    max_fingers: usize,
+    moved: bool,
+    long_pressed: bool,
+}
+
+impl TouchInput {
+    pub fn new() -> Self {
+        Self {
+            fingers: BTreeMap::new(),
+            gestures: Vec::new(),
+            max_fingers: 0,
+            moved: false,
+            long_pressed: false,
+        }
+    }
+
+    pub fn on_touch(&mut self, id: u64, phase: TouchPhase, position: Vector2<f32>, now: Instant) {
+        match phase {
+            TouchPhase::Started => {
+                if self.fingers.is_empty() {
+                    self.max_fingers = 0;
+                    self.moved = false;
+                    self.long_pressed = false;
+                }
+                self.fingers.insert(
+                    id,
+                    Finger {
+                        start: position,
+                        position,
+                        start_time: now,
+                    },
+                );
+                self.max_fingers = self.max_fingers.max(self.fingers.len());
+            }
+            TouchPhase::Moved => {
+                let previous_center = self.center();
+                let previous_spread = self.spread();
+                let finger = match self.fingers.get_mut(&id) {
+                    Some(finger) => finger,
+                    None => return,
+                };
+                finger.position = position;
+                if (finger.position - finger.start).magnitude() > TAP_SLOP {
+                    self.moved = true;
+                }
+                if !self.moved {
+                    return;
+                }
+
+                let center = self.center();
+                self.gestures.push(Gesture::Pan {
+                    position: center,
+                    delta: center - previous_center,
+                });
+                if let (Some(previous), Some(spread)) = (previous_spread, self.spread()) {
+                    if previous > 0.0 {
+                        self.gestures.push(Gesture::Pinch {
+                            center,
+                            scale: spread / previous,
+                        });
+                    }
+                }
+            }
+            TouchPhase::Ended => {
+                let finger = match self.fingers.remove(&id) {
+                    Some(finger) => finger,
+                    None => return,
+                };
+                let is_tap = self.fingers.is_empty()
+                    && self.max_fingers == 1
+                    && !self.moved
+                    && !self.long_pressed
+                    && now.duration_since(finger.start_time) < LONG_PRESS_DURATION;
+                if is_tap {
+                    self.gestures.push(Gesture::Tap { position });
+                }
+            }
+            TouchPhase::Cancelled => {
+                // the system took over the touch, nothing should be recognized
+                self.fingers.remove(&id);
+                self.moved = true;
+            }
+        }
+    }
+
+    // long presses are recognized without any event, this must be called
+    // regularly
+    pub fn update(&mut self, now: Instant) {
+        if self.max_fingers != 1 || self.moved || self.long_pressed {
+            return;
+        }
+        if let Some(finger) = self.fingers.values().next() {
+            if now.duration_since(finger.start_time) >= LONG_PRESS_DURATION {
+                self.long_pressed = true;
+                self.gestures.push(Gesture::LongPress {
+                    position: finger.position,
+                });
+            }
+        }
+    }
+
+    // clear the gestures of the tick and look for a long press in the next one
+    pub fn end_tick(&mut self, now: Instant) {
+        self.gestures.clear();
+        self.update(now);
+    }
+
+    // release all the fingers, e.g. when losing focus
+    pub fn reset(&mut self) {
+        self.fingers.clear();
+        self.gestures.clear();
+        self.max_fingers = 0;
+    }
+
+    pub fn gestures(&self) -> &[Gesture] {
+        &self.gestures
+    }
+
+    // id and position of the fingers currently touching the window
+    pub fn fingers(&self) -> impl Iterator<Item = (u64, Vector2<f32>)> + '_ {
+        self.fingers.iter().map(|(id, f)| (*id, f.position))
+    }
+
+    fn center(&self) -> Vector2<f32> {
+        if self.fingers.is_empty() {
+            return Vector2::zero();
+        }
+        let sum = self
+            .fingers
+            .values()
+            .fold(Vector2::zero(), |acc, f| acc + f.position);
+        sum / self.fingers.len() as f32
+    }
+
+    // distance between the fingers when exactly two are touching
+    fn spread(&self) -> Option<f32> {
+        let mut fingers = self.fingers.values();
+        match (fingers.next(), fingers.next(), fingers.next()) {
+            (Some(a), Some(b), None) => Some((a.position - b.position).magnitude()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(start: Instant, ms: u64) -> Instant {
+        start + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn tap() {
+        let mut touch = TouchInput::new();
+        let start = Instant::now();
+        let p = Vector2::new(100.0, 100.0);
+
+        touch.on_touch(0, TouchPhase::Started, p, at(start, 0));
+        touch.on_touch(
+            0,
+            TouchPhase::Moved,
+            p + Vector2::new(2.0, 0.0),
+            at(start, 50),
+        );
+        touch.on_touch(0, TouchPhase::Ended, p, at(start, 100));
+        assert_eq!(touch.gestures(), &[Gesture::Tap { position: p }]);
+
+        touch.end_tick(at(start, 120));
+        assert!(touch.gestures().is_empty());
+    }
+
+    #[test]
+    fn long_press_is_not_a_tap() {
+        let mut touch = TouchInput::new();
+        let start = Instant::now();
+        let p = Vector2::new(100.0, 100.0);
+
+        touch.on_touch(0, TouchPhase::Started, p, at(start, 0));
+        touch.end_tick(at(start, 200));
+        assert!(touch.gestures().is_empty());
+
+        touch.end_tick(at(start, 600));
+        assert_eq!(touch.gestures(), &[Gesture::LongPress { position: p }]);
+        touch.end_tick(at(start, 700));
+        assert!(touch.gestures().is_empty());
+
+        touch.on_touch(0, TouchPhase::Ended, p, at(start, 800));
+        assert!(touch.gestures().is_empty());
+    }
+
+    #[test]
+    fn pan() {
+        let mut touch = TouchInput::new();
+        let start = Instant::now();
+
+        touch.on_touch(0, TouchPhase::Started, Vector2::new(0.0, 0.0), start);
+        touch.on_touch(0, TouchPhase::Moved, Vector2::new(5.0, 0.0), start);
+        assert!(touch.gestures().is_empty());
+
+        touch.on_touch(0, TouchPhase::Moved, Vector2::new(20.0, 0.0), start);
+        assert_eq!(
+            touch.gestures(),
+            &[Gesture::Pan {
+                position: Vector2::new(20.0, 0.0),
+                delta: Vector2::new(15.0, 0.0),
+            }]
+        );
+
+        touch.on_touch(0, TouchPhase::Ended, Vector2::new(20.0, 0.0), start);
+        assert_eq!(touch.gestures().len(), 1);
+    }
+
+    #[test]
+    fn pinch() {
+        let mut touch = TouchInput::new();
+        let start = Instant::now();
+
+        touch.on_touch(0, TouchPhase::Started, Vector2::new(0.0, 0.0), start);
+        touch.on_touch(1, TouchPhase::Started, Vector2::new(100.0, 0.0), start);
+        touch.on_touch(1, TouchPhase::Moved, Vector2::new(200.0, 0.0), start);
+
+        let pinch = touch
+            .gestures()
+            .iter()
+            .find(|g| matches!(g, Gesture::Pinch { .. }));
+        assert_eq!(
+            pinch,
+            Some(&Gesture::Pinch {
+                center: Vector2::new(100.0, 0.0),
+                scale: 2.0,
+            })
+        );
+
+        touch.on_touch(0, TouchPhase::Ended, Vector2::new(0.0, 0.0), start);
+        touch.on_touch(1, TouchPhase::Ended, Vector2::new(200.0, 0.0), start);
+        assert!(!touch
+            .gestures()
+            .iter()
+            .any(|g| matches!(g, Gesture::Tap { .. })));
+    }
+}
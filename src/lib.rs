@@ -2,7 +2,22 @@ pub mod debug;
 mod engine;
 mod input;
 mod render;
+mod window;
 
 pub use self::engine::Application;
 pub use self::engine::Context;
 pub use self::engine::EngineBuilder;
+pub use self::input::{
+    AxisBinding, FileDropEvent, GamepadAxis, GamepadButton, GamepadEvent, GamepadId, Gesture,
+    InputBinding, InputMap, KeyChord, KeySequence, Modifier,
+};
+pub use self::render::camera::{
+    CameraController, CameraFollow, CameraId, CameraProjection, CameraSettings, CameraShake,
+    PixelPerfect, Ray, Rect, ScalingPolicy, ViewportRect, CAMERA_FLY_FORWARD, CAMERA_FLY_UP,
+    CAMERA_LOOK, CAMERA_MOVE_X, CAMERA_MOVE_Y, CAMERA_MOVE_Z, CAMERA_PAN, CAMERA_RESET_ZOOM,
+    CAMERA_ZOOM,
+};
+pub use self::render::{
+    AnimatedSprite, AnimationMode, BlendMode, RenderStats, SpriteSheet, TextureHandle, Transform2D,
+    UvRect,
+};
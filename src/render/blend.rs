@@ -0,0 +1,31 @@
+// BlendMode decides how the color of a quad is combined with what is already
+// drawn behind it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    // the alpha of the color is the opacity of the quad
+    #[default]
+    Alpha,
+    // same as Alpha for colors already multiplied by their alpha
+    Premultiplied,
+    // lights up what is behind the quad, scaled by the alpha
+    Additive,
+    // darkens what is behind the quad, the alpha is ignored
+    Multiply,
+    // the inverse of Multiply, lightens what is behind the quad
+    Screen,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 5] = [
+        BlendMode::Alpha,
+        BlendMode::Premultiplied,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+    ];
+
+    // opaque quads look the same with these modes whatever is behind them
+    pub fn keeps_opaque(&self) -> bool {
+        matches!(self, BlendMode::Alpha | BlendMode::Premultiplied)
+    }
+}
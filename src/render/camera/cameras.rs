@@ -0,0 +1,205 @@
+use std::time::Duration;
+
+use super::{CameraController, ViewportRect};
+use crate::input::InputSystem;
+use crate::render::RenderView;
+
+// CameraId identifies a camera of the engine, the main camera always exists.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CameraId(usize);
+
+impl CameraId {
+    pub const MAIN: CameraId = CameraId(0);
+}
+
+// CameraSettings decides where and when a camera is drawn.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CameraSettings {
+    // area of the window the camera is drawn in
+    pub viewport: ViewportRect,
+    // color the viewport is cleared with before drawing, None keeps what the
+    // previous cameras drew
+    pub clear_color: Option<[f32; 4]>,
+    // cameras are drawn in increasing order
+    pub order: i32,
+    // layers of quads drawn by the camera, one bit per layer
+    pub layer_mask: u32,
+}
+
+impl CameraSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_viewport(mut self, viewport: ViewportRect) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    pub fn with_clear_color(mut self, color: [f32; 4]) -> Self {
+        self.clear_color = Some(color);
+        self
+    }
+
+    pub fn with_order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn with_layer_mask(mut self, mask: u32) -> Self {
+        self.layer_mask = mask;
+        self
+    }
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            viewport: ViewportRect::FULL,
+            clear_color: None,
+            order: 0,
+            layer_mask: u32::MAX,
+        }
+    }
+}
+
+// Cameras holds the cameras drawn every frame. Only the main camera is driven
+// by the input, the others are moved by the application.
+#[derive(Debug)]
+pub struct Cameras {
+    // removed cameras leave an empty slot so that ids stay valid
+    cameras: Vec<Option<(CameraController, CameraSettings)>>,
+}
+
+impl Cameras {
+    pub fn new(main: CameraController) -> Self {
+        Self {
+            cameras: vec![Some((main, CameraSettings::default()))],
+        }
+    }
+
+    pub fn main(&self) -> &CameraController {
+        self.get(CameraId::MAIN).expect("main camera")
+    }
+
+    pub fn main_mut(&mut self) -> &mut CameraController {
+        self.get_mut(CameraId::MAIN).expect("main camera")
+    }
+
+    pub fn add(&mut self, mut camera: CameraController, settings: CameraSettings) -> CameraId {
+        camera.set_input_enabled(false);
+        camera.set_screen_area(settings.viewport);
+        self.cameras.push(Some((camera, settings)));
+        CameraId(self.cameras.len() - 1)
+    }
+
+    // the main camera can't be removed
+    pub fn remove(&mut self, id: CameraId) -> Option<CameraController> {
+        if id == CameraId::MAIN {
+            return None;
+        }
+        self.cameras
+            .get_mut(id.0)
+            .and_then(Option::take)
+            .map(|(camera, _)| camera)
+    }
+
+    pub fn get(&self, id: CameraId) -> Option<&CameraController> {
+        self.cameras
+            .get(id.0)
+            .and_then(Option::as_ref)
+            .map(|(camera, _)| camera)
+    }
+
+    pub fn get_mut(&mut self, id: CameraId) -> Option<&mut CameraController> {
+        self.cameras
+            .get_mut(id.0)
+            .and_then(Option::as_mut)
+            .map(|(camera, _)| camera)
+    }
+
+    pub fn settings(&self, id: CameraId) -> Option<CameraSettings> {
+        self.cameras
+            .get(id.0)
+            .and_then(Option::as_ref)
+            .map(|(_, settings)| *settings)
+    }
+
+    pub fn set_settings(&mut self, id: CameraId, settings: CameraSettings) {
+        if let Some((camera, current)) = self.cameras.get_mut(id.0).and_then(Option::as_mut) {
+            camera.set_screen_area(settings.viewport);
+            *current = settings;
+        }
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut CameraController> {
+        self.cameras.iter_mut().flatten().map(|(camera, _)| camera)
+    }
+
+    pub fn resize(&mut self, width: f32, height: f32) {
+        self.iter_mut()
+            .for_each(|camera| camera.resize(width, height));
+    }
+
+    pub fn on_update(&mut self, input: &InputSystem, scale_factor: f64, delta: Duration) {
+        self.iter_mut()
+            .for_each(|camera| camera.on_update(input, scale_factor, delta));
+    }
+
+    pub fn is_cursor_locked(&self) -> bool {
+        self.main().is_cursor_locked()
+    }
+
+    // cameras to draw this frame, in draw order
+    pub fn views(&self) -> Vec<RenderView> {
+        let mut cameras: Vec<_> = self.cameras.iter().flatten().collect();
+        // stable: cameras with the same order are drawn in creation order
+        cameras.sort_by_key(|(_, settings)| settings.order);
+        cameras
+            .into_iter()
+            .map(|(camera, settings)| RenderView {
+                view_projection: camera.render_view_projection_matrix(),
+                viewport: camera.viewport(),
+                clear_color: settings.clear_color,
+                layer_mask: settings.layer_mask,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::camera::CameraProjection;
+
+    #[test]
+    fn views_are_sorted_and_ids_stay_valid() {
+        let controller = || CameraController::new(CameraProjection::Orthographic, 800.0, 600.0);
+        let mut cameras = Cameras::new(controller());
+        let minimap = cameras.add(
+            controller(),
+            CameraSettings::new()
+                .with_order(-1)
+                .with_layer_mask(0b10)
+                .with_viewport(ViewportRect {
+                    x: 0.75,
+                    y: 0.0,
+                    width: 0.25,
+                    height: 0.25,
+                }),
+        );
+        let overlay = cameras.add(controller(), CameraSettings::new().with_order(1));
+
+        let views = cameras.views();
+        assert_eq!(views.len(), 3);
+        assert_eq!(views[0].layer_mask, 0b10);
+        assert_eq!(views[0].viewport.x, 0.75);
+        assert_eq!(views[1].viewport, ViewportRect::FULL);
+
+        assert!(cameras.remove(CameraId::MAIN).is_none());
+        assert!(cameras.remove(minimap).is_some());
+        assert!(cameras.get(minimap).is_none());
+        assert!(cameras.get(overlay).is_some());
+        assert_eq!(cameras.views().len(), 2);
+    }
+}
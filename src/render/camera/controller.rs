@@ -3,23 +3,65 @@ use std::{
     time::Duration,
 };
 
-use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Vector3};
-use winit::event::VirtualKeyCode;
+use cgmath::{
+    EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Vector2, Vector3, Vector4, Zero,
+};
+use winit::event::{MouseButton, VirtualKeyCode};
+
+use super::{
+    follow::FollowState, Camera, CameraFollow, CameraProjection, CameraShake, PixelPerfect, Ray,
+    Rect, ScalingPolicy, ViewportRect,
+};
+use crate::input::{AxisBinding, Gesture, InputBinding, InputMap, InputSystem};
+use crate::render::{LowResolutionTarget, VULKAN_COORD_MAGIC_PROJ};
+
+// names of the actions and axes driving the camera, they can be rebound
+// through the engine InputMap
+//
+// panning moves along the world axes: CAMERA_MOVE_Y is up and CAMERA_MOVE_Z
+// is forward, while flying uses its own axes relative to the look direction
+pub const CAMERA_MOVE_X: &str = "camera_move_x";
+pub const CAMERA_MOVE_Y: &str = "camera_move_y";
+pub const CAMERA_MOVE_Z: &str = "camera_move_z";
+pub const CAMERA_FLY_FORWARD: &str = "camera_fly_forward";
+pub const CAMERA_FLY_UP: &str = "camera_fly_up";
+pub const CAMERA_ZOOM: &str = "camera_zoom";
+pub const CAMERA_RESET_ZOOM: &str = "camera_reset_zoom";
+pub const CAMERA_PAN: &str = "camera_pan";
+pub const CAMERA_LOOK: &str = "camera_look";
+
+// distance to the window borders, in logical pixels, at which edge scrolling
+// starts
+const EDGE_SCROLL_MARGIN: f32 = 16.0;
 
-use super::ortho::CameraOrthographic;
-use crate::Context;
+// the fly camera cannot look further up or down than this, in radians
+const PITCH_LIMIT: f32 = 1.55;
 
 const HORIZONTAL_VEC: Vector3<f32> = Vector3::new(1.0, 0.0, 0.0);
 const VERTICAL_VEC: Vector3<f32> = Vector3::new(0.0, 1.0, 0.0);
 
-#[derive(Debug, Clone)]
+// CameraController moves the camera from the input. The orthographic camera
+// pans over the XY plane, the perspective camera flies like a first person
+// camera while CAMERA_LOOK is held.
+#[derive(Debug)]
 pub struct CameraController {
     speed_base: f32,
     pos: Vector3<f32>,
     target: Vector3<f32>,
     up: Vector3<f32>,
-    camera: CameraOrthographic,
+    camera: Box<dyn Camera>,
     view: Matrix4<f32>,
+    // size of the window and area of the window the camera is drawn in
+    window_size: Vector2<f32>,
+    area: ViewportRect,
+    input_enabled: bool,
+
+    fly_enabled: bool,
+    fly_speed: f32,
+    look_sensitivity: f32,
+    yaw: f32,
+    pitch: f32,
+    cursor_locked: bool,
 
     zoom_target: f32,
     zoom_min: f32,
@@ -27,31 +69,74 @@ pub struct CameraController {
     zoom_sensitivity: f32,
     zoom_speed: f32,
     zoom_deadzone: f32,
+    // cursor position the zoom is centered on, in logical pixels
+    zoom_anchor: Option<Vector2<f32>>,
+
+    // world position grabbed when the pan drag started
+    drag_anchor: Option<Vector2<f32>>,
+    edge_scroll: bool,
+
+    follow: Option<FollowState>,
+    shake: CameraShake,
 }
 
 impl CameraController {
-    pub fn new(camera: CameraOrthographic) -> Self {
+    pub fn new(projection: CameraProjection, width: f32, height: f32) -> Self {
+        let camera = projection.create(width, height);
         let mut controller = Self {
             speed_base: 1.0,
             pos: Vector3::new(0.0, 0.0, 2.0),
             target: Vector3::new(0.0, 0.0, -1.0),
             up: Vector3::new(0.0, 1.0, 0.0),
+            zoom_target: camera.zoom(),
             camera,
             view: Matrix4::identity(),
-            zoom_target: camera.zoom(),
+            window_size: Vector2::new(width, height),
+            area: ViewportRect::FULL,
+            input_enabled: true,
+            fly_enabled: projection == CameraProjection::Perspective,
+            fly_speed: 2.0,
+            look_sensitivity: 0.002,
+            yaw: 0.0,
+            pitch: 0.0,
+            cursor_locked: false,
             zoom_min: 0.01,
             zoom_max: 10.0,
             zoom_sensitivity: 0.1,
             zoom_speed: 10.0,
             zoom_deadzone: 0.1,
+            zoom_anchor: None,
+            drag_anchor: None,
+            edge_scroll: false,
+            follow: None,
+            shake: CameraShake::default(),
         };
         controller.compute_view_matrix();
         controller
     }
 
-    pub fn on_update(&mut self, ctx: Context, delta: Duration) {
+    pub fn on_update(&mut self, input: &InputSystem, scale_factor: f64, delta: Duration) {
+        self.camera.set_scale_factor(scale_factor);
+        self.shake.update(delta.as_secs_f32());
         self.compute_view_matrix();
 
+        // the followed target drives the orthographic camera
+        let following = self.follow.is_some() && !self.fly_enabled;
+        if self.input_enabled {
+            self.update_from_input(input, scale_factor, delta, following);
+        }
+        if following {
+            self.update_follow(delta);
+        }
+    }
+
+    fn update_from_input(
+        &mut self,
+        input: &InputSystem,
+        scale_factor: f64,
+        delta: Duration,
+        following: bool,
+    ) {
         let speed = self.speed_base * delta.as_secs_f32();
 
         // adapt movement speed based on zoom level
@@ -67,75 +152,464 @@ impl CameraController {
 
         let movement_speed = speed * speed_modifier;
 
-        // move camera => Z
-        if ctx.is_key_pressed(VirtualKeyCode::Q) {
-            self.move_backward(movement_speed)
-        }
-        if ctx.is_key_pressed(VirtualKeyCode::E) {
-            self.move_forward(movement_speed)
-        }
-        // move camera => Y
-        if ctx.is_key_pressed(VirtualKeyCode::W) {
-            self.move_up(movement_speed)
-        }
-        if ctx.is_key_pressed(VirtualKeyCode::S) {
-            self.move_down(movement_speed)
-        }
-        // move camera => X
-        if ctx.is_key_pressed(VirtualKeyCode::A) {
-            self.move_left(movement_speed)
-        }
-        if ctx.is_key_pressed(VirtualKeyCode::D) {
-            self.move_right(movement_speed)
+        // cursor position in logical pixels, unless the cursor is outside of
+        // the window or consumed
+        let cursor = input.cursor().map(|cursor| {
+            let cursor = cursor.to_logical::<f32>(scale_factor);
+            Vector2::new(cursor.x, cursor.y)
+        });
+
+        if self.fly_enabled {
+            self.fly(input, delta);
+        } else if !following {
+            self.pan(input, scale_factor, cursor, movement_speed);
         }
 
-        // on scroll, update zoom_target
-        self.zoom_target -= ctx.mouse_scoll_y() * self.zoom_sensitivity;
+        // on scroll, update zoom_target proportionally to the amount scrolled
+        let zoom = input.axis(CAMERA_ZOOM);
+        let pixel_perfect = self.camera.pixel_perfect().is_some();
+        if zoom != 0.0 {
+            if pixel_perfect {
+                // one scale per scroll step
+                let scale = PixelPerfect::scale_for_zoom(self.zoom_target) as f32;
+                self.zoom_target = 1.0 / (scale + zoom.signum()).max(1.0);
+            } else {
+                self.zoom_target -= zoom * self.zoom_sensitivity;
+            }
+            // zooming a perspective camera changes its field of view, the
+            // point under the cursor cannot be kept in place
+            self.zoom_anchor = cursor.filter(|_| !self.fly_enabled && !following);
+        }
 
         // clamp zoom target between min and max
         self.zoom_target = clamp(self.zoom_target, self.zoom_min, self.zoom_max);
 
-        // move camera (lerp) towards zoom_target
-        if (self.camera.zoom() - self.zoom_target).abs() > self.zoom_deadzone / 2.0 {
+        // move camera (lerp) towards zoom_target, pixel perfect cameras jump
+        // from one scale to the next
+        if pixel_perfect {
+            if self.camera.zoom()
+                != PixelPerfect::zoom_for_scale(PixelPerfect::scale_for_zoom(self.zoom_target))
+            {
+                self.zoom_around(self.zoom_target);
+            }
+            self.zoom_anchor = None;
+        } else if (self.camera.zoom() - self.zoom_target).abs() > self.zoom_deadzone / 2.0 {
             let zoom_amount = lerp(
                 self.camera.zoom(),
                 self.zoom_target,
                 speed * self.zoom_speed,
             );
-            self.camera.set_zoom(zoom_amount);
+            self.zoom_around(zoom_amount);
+        } else {
+            self.zoom_anchor = None;
         }
 
         // reset zoom
-        if ctx.is_key_pressed(VirtualKeyCode::Z) {
+        if input.is_action_pressed(CAMERA_RESET_ZOOM) {
             self.camera.reset_zoom();
             self.zoom_target = self.camera.zoom();
+            self.zoom_anchor = None;
+        }
+    }
+
+    fn update_follow(&mut self, delta: Duration) {
+        let half_extent = self
+            .visible_rect()
+            .map(|rect| rect.size() / 2.0)
+            .unwrap_or_else(Vector2::zero);
+        if let Some(follow) = self.follow.as_mut() {
+            let center = follow.update(self.pos.truncate(), half_extent, delta.as_secs_f32());
+            self.pos.x = center.x;
+            self.pos.y = center.y;
+            self.compute_view_matrix();
+        }
+    }
+
+    // move the orthographic camera over the XY plane
+    fn pan(
+        &mut self,
+        input: &InputSystem,
+        scale_factor: f64,
+        cursor: Option<Vector2<f32>>,
+        movement_speed: f32,
+    ) {
+        // move camera => Z
+        self.move_forward(movement_speed * input.axis(CAMERA_MOVE_Z));
+        // move camera => Y
+        self.move_up(movement_speed * input.axis(CAMERA_MOVE_Y));
+        // move camera => X
+        self.move_right(movement_speed * input.axis(CAMERA_MOVE_X));
+
+        // move camera when the cursor is close to the window borders
+        if let Some(cursor) = cursor.filter(|_| self.edge_scroll && self.drag_anchor.is_none()) {
+            let (width, height) = self.window_size.into();
+            let edge = |v: f32, max: f32| {
+                if v < EDGE_SCROLL_MARGIN {
+                    -1.0
+                } else if v > max - EDGE_SCROLL_MARGIN {
+                    1.0
+                } else {
+                    0.0
+                }
+            };
+            self.move_right(movement_speed * edge(cursor.x, width));
+            self.move_up(-movement_speed * edge(cursor.y, height));
+        }
+        self.compute_view_matrix();
+
+        // drag to pan, keeping the grabbed world point under the cursor
+        match cursor.filter(|_| input.is_action_pressed(CAMERA_PAN)) {
+            Some(cursor) => match self.drag_anchor {
+                Some(anchor) => {
+                    let grabbed = self.screen_to_world(cursor);
+                    self.pos += (anchor - grabbed).extend(0.0);
+                    self.compute_view_matrix();
+                }
+                None => self.drag_anchor = Some(self.screen_to_world(cursor)),
+            },
+            None => self.drag_anchor = None,
+        }
+
+        // pan with the fingers and pinch to zoom
+        for gesture in input.gestures() {
+            match gesture.to_logical(scale_factor) {
+                Gesture::Pan { position, delta } => {
+                    // keep the world point under the fingers in place
+                    let world_delta =
+                        self.screen_to_world(position) - self.screen_to_world(position - delta);
+                    self.pos -= world_delta.extend(0.0);
+                    self.compute_view_matrix();
+                }
+                Gesture::Pinch { center, scale } if scale > 0.0 => {
+                    self.zoom_target /= scale;
+                    self.zoom_anchor = Some(center);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // rotate with the mouse while CAMERA_LOOK is held and move relatively to
+    // the direction the perspective camera is looking at
+    fn fly(&mut self, input: &InputSystem, delta: Duration) {
+        self.cursor_locked = input.is_action_pressed(CAMERA_LOOK);
+        if self.cursor_locked {
+            let motion = input.mouse_motion();
+            self.yaw += motion.x * self.look_sensitivity;
+            self.pitch = clamp(
+                self.pitch - motion.y * self.look_sensitivity,
+                -PITCH_LIMIT,
+                PITCH_LIMIT,
+            );
+            self.target = Vector3::new(
+                self.yaw.sin() * self.pitch.cos(),
+                self.pitch.sin(),
+                -self.yaw.cos() * self.pitch.cos(),
+            );
+        }
+
+        let speed = self.fly_speed * delta.as_secs_f32();
+        let right = self.target.cross(VERTICAL_VEC).normalize();
+        self.pos += self.target * speed * input.axis(CAMERA_FLY_FORWARD);
+        self.pos += right * speed * input.axis(CAMERA_MOVE_X);
+        self.pos += VERTICAL_VEC * speed * input.axis(CAMERA_FLY_UP);
+        self.compute_view_matrix();
+    }
+
+    // register the default camera bindings, leaving the ones already present
+    // in the map untouched
+    pub fn bind_defaults(map: &mut InputMap) {
+        let key = InputBinding::Key;
+        let axes = [
+            (
+                CAMERA_MOVE_X,
+                key(VirtualKeyCode::D),
+                key(VirtualKeyCode::A),
+            ),
+            (
+                CAMERA_MOVE_Y,
+                key(VirtualKeyCode::W),
+                key(VirtualKeyCode::S),
+            ),
+            (
+                CAMERA_MOVE_Z,
+                key(VirtualKeyCode::E),
+                key(VirtualKeyCode::Q),
+            ),
+            (
+                CAMERA_FLY_FORWARD,
+                key(VirtualKeyCode::W),
+                key(VirtualKeyCode::S),
+            ),
+            (
+                CAMERA_FLY_UP,
+                key(VirtualKeyCode::E),
+                key(VirtualKeyCode::Q),
+            ),
+            (
+                CAMERA_ZOOM,
+                InputBinding::ScrollUp,
+                InputBinding::ScrollDown,
+            ),
+        ];
+        for (name, positive, negative) in axes {
+            if !map.has_axis(name) {
+                map.bind_axis(name, AxisBinding::new(positive, negative));
+            }
+        }
+        if !map.has_action(CAMERA_RESET_ZOOM) {
+            map.bind_action(CAMERA_RESET_ZOOM, key(VirtualKeyCode::Z));
+        }
+        if !map.has_action(CAMERA_PAN) {
+            map.bind_action(CAMERA_PAN, InputBinding::MouseButton(MouseButton::Middle));
+        }
+        if !map.has_action(CAMERA_LOOK) {
+            map.bind_action(CAMERA_LOOK, InputBinding::MouseButton(MouseButton::Right));
+        }
+    }
+
+    // follow a target with the given settings, the target is set with
+    // set_follow_target
+    pub fn set_follow(&mut self, config: CameraFollow) {
+        match self.follow.as_mut() {
+            Some(follow) => follow.set_config(config),
+            None => self.follow = Some(FollowState::new(config)),
+        }
+    }
+
+    // position to follow, starts following with the default settings if the
+    // camera was not following anything
+    pub fn set_follow_target(&mut self, target: Vector2<f32>) {
+        self.follow
+            .get_or_insert_with(|| FollowState::new(CameraFollow::default()))
+            .set_target(target);
+    }
+
+    pub fn stop_follow(&mut self) {
+        self.follow = None;
+    }
+
+    // add trauma to shake the camera, between 0.0 and 1.0
+    pub fn add_shake(&mut self, trauma: f32) {
+        self.shake.add_trauma(trauma)
+    }
+
+    pub fn set_shake(&mut self, settings: CameraShake) {
+        self.shake.configure(settings)
+    }
+
+    pub fn position(&self) -> Vector3<f32> {
+        self.pos
+    }
+
+    // the cursor must be hidden and grabbed while the fly camera is rotated
+    pub fn is_cursor_locked(&self) -> bool {
+        self.cursor_locked
+    }
+
+    // move the camera when the cursor gets close to the window borders
+    pub fn set_edge_scroll(&mut self, b: bool) {
+        self.edge_scroll = b;
+    }
+
+    // width and height are the size of the window, in logical pixels
+    pub fn resize(&mut self, width: f32, height: f32) {
+        if width == 0.0 || height == 0.0 {
+            return;
+        }
+        self.window_size = Vector2::new(width, height);
+        self.camera
+            .resize(width * self.area.width, height * self.area.height)
+    }
+
+    // area of the window the camera is drawn in, for split screen or minimaps
+    pub fn set_screen_area(&mut self, area: ViewportRect) {
+        self.area = area;
+        self.resize(self.window_size.x, self.window_size.y);
+    }
+
+    pub fn screen_area(&self) -> ViewportRect {
+        self.area
+    }
+
+    // cameras not driven by the input only follow their target and shake
+    pub fn set_input_enabled(&mut self, b: bool) {
+        self.input_enabled = b;
+        if !b {
+            self.cursor_locked = false;
+            self.drag_anchor = None;
+        }
+    }
+
+    pub fn set_scaling_policy(&mut self, policy: ScalingPolicy) {
+        self.camera.set_scaling_policy(policy)
+    }
+
+    // pixel perfect cameras ignore the scaling policy, only the orthographic
+    // camera supports it
+    pub fn set_pixel_perfect(&mut self, pixel_perfect: Option<PixelPerfect>) {
+        self.camera.set_pixel_perfect(pixel_perfect);
+        self.zoom_target = self.camera.zoom();
+        self.zoom_anchor = None;
+        self.compute_view_matrix();
+    }
+
+    pub fn pixel_perfect(&self) -> Option<PixelPerfect> {
+        self.camera.pixel_perfect()
+    }
+
+    // offscreen target the camera is drawn to before being upscaled, when the
+    // pixel perfect camera draws at low resolution
+    pub fn low_resolution_target(&self) -> Option<LowResolutionTarget> {
+        if !self.camera.pixel_perfect()?.low_resolution {
+            return None;
         }
+        Some(LowResolutionTarget {
+            size: self.camera.texels()?,
+            viewport: self.viewport(),
+        })
     }
 
+    // area of the window the scene is rendered to: the viewport of the camera
+    // scaling policy inside the screen area
+    pub fn viewport(&self) -> ViewportRect {
+        let viewport = self.camera.viewport();
+        ViewportRect {
+            x: self.area.x + viewport.x * self.area.width,
+            y: self.area.y + viewport.y * self.area.height,
+            width: viewport.width * self.area.width,
+            height: viewport.height * self.area.height,
+        }
+    }
+
+    // matrix of the gameplay view, used by every conversion between screen and
+    // world positions
     pub fn view_projection_matrix(&self) -> Matrix4<f32> {
         self.camera.projection_matrix().mul(self.view)
     }
 
+    // matrix the scene is rendered with: the gameplay view with the shake
+    pub fn render_view_projection_matrix(&self) -> Matrix4<f32> {
+        self.camera
+            .projection_matrix()
+            .mul(self.shake.view_offset())
+            .mul(self.view)
+    }
+
+    // Screen positions are expressed in logical window pixels, with the
+    // origin at the top-left corner and Y pointing downwards.
+
+    // position on the z = 0 plane, where the quads are drawn, under the given
+    // screen position
+    pub fn screen_to_world(&self, position: Vector2<f32>) -> Vector2<f32> {
+        let ray = self.pick_ray(position);
+        ray.intersect_plane_z(0.0).unwrap_or(ray.origin).truncate()
+    }
+
+    // screen position of a world position, None when it is behind the camera
+    pub fn world_to_screen(&self, position: Vector3<f32>) -> Option<Vector2<f32>> {
+        let clip = self.clip_matrix().mul(position.extend(1.0));
+        if clip.w <= 0.0 {
+            return None;
+        }
+        Some(self.ndc_to_screen(clip.truncate().truncate() / clip.w))
+    }
+
+    // ray starting on the near plane and going through the given screen
+    // position, parallel to the view direction for the orthographic camera
+    pub fn pick_ray(&self, position: Vector2<f32>) -> Ray {
+        let ndc = self.screen_to_ndc(position);
+        let inverse = self
+            .clip_matrix()
+            .invert()
+            .unwrap_or_else(Matrix4::identity);
+        let unproject = |z: f32| {
+            let world = inverse.mul(Vector4::new(ndc.x, ndc.y, z, 1.0));
+            world.truncate() / world.w
+        };
+        let near = unproject(0.0);
+        Ray::new(near, unproject(1.0) - near)
+    }
+
+    // area of the z = 0 plane visible through the viewport, None when the
+    // perspective camera looks above the horizon
+    pub fn visible_rect(&self) -> Option<Rect> {
+        let (width, height) = self.window_size.into();
+        let viewport = self.viewport();
+        let (left, top) = (viewport.x * width, viewport.y * height);
+        let (right, bottom) = (
+            left + viewport.width * width,
+            top + viewport.height * height,
+        );
+        let corners = [(left, top), (right, top), (right, bottom), (left, bottom)]
+            .into_iter()
+            .map(|(x, y)| {
+                self.pick_ray(Vector2::new(x, y))
+                    .intersect_plane_z(0.0)
+                    .map(|p| p.truncate())
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Rect::from_points(&corners)
+    }
+
+    // view projection followed by the conversion to the Vulkan clip space done
+    // in Renderer2D::end_frame, so that Y points downwards like on screen
+    fn clip_matrix(&self) -> Matrix4<f32> {
+        VULKAN_COORD_MAGIC_PROJ.mul(self.view_projection_matrix())
+    }
+
+    fn screen_to_ndc(&self, position: Vector2<f32>) -> Vector2<f32> {
+        let (width, height) = self.window_size.into();
+        let viewport = self.viewport();
+        let x = (position.x / width - viewport.x) / viewport.width;
+        let y = (position.y / height - viewport.y) / viewport.height;
+        Vector2::new(2.0 * x - 1.0, 2.0 * y - 1.0)
+    }
+
+    fn ndc_to_screen(&self, ndc: Vector2<f32>) -> Vector2<f32> {
+        let (width, height) = self.window_size.into();
+        let viewport = self.viewport();
+        let x = (ndc.x + 1.0) / 2.0 * viewport.width + viewport.x;
+        let y = (ndc.y + 1.0) / 2.0 * viewport.height + viewport.y;
+        Vector2::new(x * width, y * height)
+    }
+
+    // set the zoom, keeping the world point under the zoom anchor in place
+    fn zoom_around(&mut self, zoom: f32) {
+        let anchor = match self.zoom_anchor {
+            Some(anchor) => anchor,
+            None => return self.camera.set_zoom(zoom),
+        };
+        let before = self.screen_to_world(anchor);
+        self.camera.set_zoom(zoom);
+        let after = self.screen_to_world(anchor);
+        self.pos += (before - after).extend(0.0);
+        self.compute_view_matrix();
+    }
+
+    // the shake is not part of the view, it only moves what is rendered
     fn compute_view_matrix(&mut self) {
+        // pixel perfect cameras are snapped to whole texels when rendering
+        // only, so that slow movements still add up
+        let pos = match self.camera.pixel_perfect() {
+            Some(pixel_perfect) => Vector3::new(
+                pixel_perfect.snap(self.pos.x),
+                pixel_perfect.snap(self.pos.y),
+                self.pos.z,
+            ),
+            None => self.pos,
+        };
         self.view = Matrix4::look_at_rh(
-            Point3::from_vec(self.pos),
-            Point3::from_vec(self.pos.add(self.target)),
+            Point3::from_vec(pos),
+            Point3::from_vec(pos.add(self.target)),
             self.up,
-        )
+        );
     }
 
     fn move_forward(&mut self, speed: f32) {
         self.pos = self.pos.add(self.target.mul(speed))
     }
-    fn move_backward(&mut self, speed: f32) {
-        self.pos = self.pos.sub(self.target.mul(speed))
-    }
 
-    fn move_left(&mut self, speed: f32) {
-        self.pos = self
-            .pos
-            .sub(self.target.normalize().cross(VERTICAL_VEC).mul(speed))
-    }
     fn move_right(&mut self, speed: f32) {
         self.pos = self
             .pos
@@ -146,11 +620,6 @@ impl CameraController {
             .pos
             .sub(self.target.normalize().cross(HORIZONTAL_VEC).mul(speed))
     }
-    fn move_down(&mut self, speed: f32) {
-        self.pos = self
-            .pos
-            .add(self.target.normalize().cross(HORIZONTAL_VEC).mul(speed))
-    }
 }
 
 fn clamp(v: f32, min: f32, max: f32) -> f32 {
@@ -166,3 +635,76 @@ fn clamp(v: f32, min: f32, max: f32) -> f32 {
 fn lerp(start: f32, end: f32, amount: f32) -> f32 {
     start + (end - start) * amount
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_keeps_anchor_in_place() {
+        let mut controller = CameraController::new(CameraProjection::Orthographic, 800.0, 600.0);
+        let anchor = Vector2::new(600.0, 150.0);
+        let before = controller.screen_to_world(anchor);
+
+        controller.zoom_anchor = Some(anchor);
+        controller.zoom_around(0.5);
+        let after = controller.screen_to_world(anchor);
+
+        assert!((before - after).magnitude() < 1e-4);
+        assert_eq!(controller.camera.zoom(), 0.5);
+    }
+
+    #[test]
+    fn perspective_screen_to_world_hits_the_quad_plane() {
+        let controller = CameraController::new(CameraProjection::Perspective, 800.0, 600.0);
+        let center = controller.screen_to_world(Vector2::new(400.0, 300.0));
+        assert!(center.magnitude() < 1e-4);
+
+        // the right border of the window is further away than in orthographic
+        let right = controller.screen_to_world(Vector2::new(800.0, 300.0));
+        assert!(right.x > 1.0 && right.y.abs() < 1e-4);
+    }
+
+    #[test]
+    fn screen_world_roundtrip() {
+        for projection in [
+            CameraProjection::Orthographic,
+            CameraProjection::Perspective,
+        ] {
+            let mut controller = CameraController::new(projection, 800.0, 600.0);
+            controller.set_scaling_policy(ScalingPolicy::Fit { aspect_ratio: 1.0 });
+
+            let screen = Vector2::new(420.0, 130.0);
+            let world = controller.screen_to_world(screen);
+            let back = controller.world_to_screen(world.extend(0.0)).unwrap();
+            assert!((back - screen).magnitude() < 1e-2, "{:?}", projection);
+
+            // world Y points upwards
+            assert!(controller.screen_to_world(Vector2::new(400.0, 0.0)).y > 0.0);
+        }
+    }
+
+    #[test]
+    fn orthographic_visible_rect() {
+        let controller = CameraController::new(CameraProjection::Orthographic, 800.0, 600.0);
+        let rect = controller.visible_rect().unwrap();
+        assert!((rect.min - Vector2::new(-4.0 / 3.0, -1.0)).magnitude() < 1e-4);
+        assert!((rect.max - Vector2::new(4.0 / 3.0, 1.0)).magnitude() < 1e-4);
+    }
+
+    #[test]
+    fn shake_does_not_move_gameplay_positions() {
+        let mut controller = CameraController::new(CameraProjection::Orthographic, 800.0, 600.0);
+        controller.set_input_enabled(false);
+        let cursor = Vector2::new(600.0, 150.0);
+        let before = controller.screen_to_world(cursor);
+
+        controller.add_shake(1.0);
+        controller.on_update(&InputSystem::new(), 1.0, Duration::from_millis(50));
+        assert_ne!(
+            controller.render_view_projection_matrix(),
+            controller.view_projection_matrix()
+        );
+        assert_eq!(controller.screen_to_world(cursor), before);
+    }
+}
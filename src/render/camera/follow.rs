@@ -0,0 +1,233 @@
+use cgmath::{Vector2, Zero};
+
+use super::Rect;
+
+// CameraFollow configures how the camera tracks a target, see
+// CameraController::set_follow.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CameraFollow {
+    // approximate time, in seconds, to reach the target
+    pub smooth_time: f32,
+    // half size of the area around the camera center in which the target can
+    // move without moving the camera
+    pub dead_zone: Vector2<f32>,
+    // how far ahead of the target the camera looks, in seconds of the target
+    // velocity
+    pub look_ahead: f32,
+    // area the visible part of the world must stay in
+    pub bounds: Option<Rect>,
+}
+
+impl CameraFollow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_smooth_time(mut self, t: f32) -> Self {
+        self.smooth_time = t;
+        self
+    }
+
+    pub fn with_dead_zone(mut self, half_size: Vector2<f32>) -> Self {
+        self.dead_zone = half_size;
+        self
+    }
+
+    pub fn with_look_ahead(mut self, t: f32) -> Self {
+        self.look_ahead = t;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Rect) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+}
+
+impl Default for CameraFollow {
+    fn default() -> Self {
+        Self {
+            smooth_time: 0.2,
+            dead_zone: Vector2::zero(),
+            look_ahead: 0.0,
+            bounds: None,
+        }
+    }
+}
+
+// FollowState is the state of the camera while following a target.
+#[derive(Debug, Copy, Clone)]
+pub struct FollowState {
+    config: CameraFollow,
+    target: Option<Vector2<f32>>,
+    previous_target: Option<Vector2<f32>>,
+    // point the dead zone is centered on
+    focus: Option<Vector2<f32>>,
+    velocity: Vector2<f32>,
+}
+
+impl FollowState {
+    pub fn new(config: CameraFollow) -> Self {
+        Self {
+            config,
+            target: None,
+            previous_target: None,
+            focus: None,
+            velocity: Vector2::zero(),
+        }
+    }
+
+    pub fn set_config(&mut self, config: CameraFollow) {
+        self.config = config;
+    }
+
+    pub fn set_target(&mut self, target: Vector2<f32>) {
+        self.target = Some(target);
+    }
+
+    // new camera center, half_extent is half the size of the visible area
+    pub fn update(
+        &mut self,
+        center: Vector2<f32>,
+        half_extent: Vector2<f32>,
+        dt: f32,
+    ) -> Vector2<f32> {
+        let target = match self.target {
+            Some(target) => target,
+            None => return center,
+        };
+        if dt <= 0.0 {
+            return center;
+        }
+
+        let target_velocity = match self.previous_target {
+            Some(previous) => (target - previous) / dt,
+            None => Vector2::zero(),
+        };
+        self.previous_target = Some(target);
+
+        // move the dead zone only when the target pushes against its borders
+        let focus = self.focus.unwrap_or(center);
+        let dead_zone = self.config.dead_zone;
+        let focus = Vector2::new(
+            push(focus.x, target.x, dead_zone.x),
+            push(focus.y, target.y, dead_zone.y),
+        );
+        self.focus = Some(focus);
+
+        let desired = focus + target_velocity * self.config.look_ahead;
+        let desired = match self.config.bounds {
+            Some(bounds) => clamp_to_bounds(desired, half_extent, &bounds),
+            None => desired,
+        };
+
+        let center = smooth_damp(
+            center,
+            desired,
+            &mut self.velocity,
+            self.config.smooth_time,
+            dt,
+        );
+        match self.config.bounds {
+            Some(bounds) => clamp_to_bounds(center, half_extent, &bounds),
+            None => center,
+        }
+    }
+}
+
+// move the center of a zone of the given half size just enough for it to
+// contain the value
+fn push(center: f32, value: f32, half_size: f32) -> f32 {
+    if value > center + half_size {
+        value - half_size
+    } else if value < center - half_size {
+        value + half_size
+    } else {
+        center
+    }
+}
+
+// critically damped spring towards the target, velocity is kept between calls
+//
+// ref: Game Programming Gems 4, chapter 1.10
+fn smooth_damp(
+    current: Vector2<f32>,
+    target: Vector2<f32>,
+    velocity: &mut Vector2<f32>,
+    smooth_time: f32,
+    dt: f32,
+) -> Vector2<f32> {
+    if smooth_time <= 0.0 {
+        *velocity = Vector2::zero();
+        return target;
+    }
+    let omega = 2.0 / smooth_time;
+    let x = omega * dt;
+    let exp = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+    let change = current - target;
+    let temp = (*velocity + change * omega) * dt;
+    *velocity = (*velocity - temp * omega) * exp;
+    target + (change + temp) * exp
+}
+
+// keep the visible area inside the bounds, centering it on the bounds when it
+// is bigger than them
+fn clamp_to_bounds(center: Vector2<f32>, half_extent: Vector2<f32>, bounds: &Rect) -> Vector2<f32> {
+    let clamp_axis = |c: f32, half: f32, min: f32, max: f32| {
+        if max - min < 2.0 * half {
+            (min + max) / 2.0
+        } else {
+            c.clamp(min + half, max - half)
+        }
+    };
+    Vector2::new(
+        clamp_axis(center.x, half_extent.x, bounds.min.x, bounds.max.x),
+        clamp_axis(center.y, half_extent.y, bounds.min.y, bounds.max.y),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::InnerSpace;
+
+    use super::*;
+
+    #[test]
+    fn smooth_damp_converges_without_overshoot() {
+        let target = Vector2::new(10.0, 0.0);
+        let mut current = Vector2::zero();
+        let mut velocity = Vector2::zero();
+        for _ in 0..240 {
+            current = smooth_damp(current, target, &mut velocity, 0.2, 1.0 / 120.0);
+            assert!(current.x <= target.x);
+        }
+        assert!((current - target).magnitude() < 1e-2);
+    }
+
+    #[test]
+    fn dead_zone() {
+        let config = CameraFollow::new()
+            .with_smooth_time(0.0)
+            .with_dead_zone(Vector2::new(2.0, 1.0));
+        let mut follow = FollowState::new(config);
+        let half_extent = Vector2::new(4.0, 3.0);
+
+        follow.set_target(Vector2::new(1.5, 0.5));
+        let center = follow.update(Vector2::zero(), half_extent, 0.1);
+        assert_eq!(center, Vector2::zero());
+
+        follow.set_target(Vector2::new(5.0, 0.0));
+        let center = follow.update(center, half_extent, 0.1);
+        assert_eq!(center, Vector2::new(3.0, 0.0));
+    }
+
+    #[test]
+    fn bounds() {
+        let bounds = Rect::new(Vector2::new(0.0, 0.0), Vector2::new(10.0, 4.0));
+        let half_extent = Vector2::new(2.0, 3.0);
+
+        // the visible area is taller than the bounds: centered vertically
+        let center = clamp_to_bounds(Vector2::new(-5.0, 8.0), half_extent, &bounds);
+        assert_eq!(center, Vector2::new(2.0, 2.0));
+    }
+}
@@ -0,0 +1,81 @@
+use cgmath::{InnerSpace, Vector2, Vector3};
+
+// Rect is an axis aligned rectangle in world space.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rect {
+    pub min: Vector2<f32>,
+    pub max: Vector2<f32>,
+}
+
+impl Rect {
+    pub fn new(min: Vector2<f32>, max: Vector2<f32>) -> Self {
+        Self { min, max }
+    }
+
+    // smallest rectangle containing all the points
+    pub fn from_points(points: &[Vector2<f32>]) -> Option<Self> {
+        let (first, rest) = points.split_first()?;
+        let rect = rest.iter().fold(Rect::new(*first, *first), |r, p| {
+            Rect::new(
+                Vector2::new(r.min.x.min(p.x), r.min.y.min(p.y)),
+                Vector2::new(r.max.x.max(p.x), r.max.y.max(p.y)),
+            )
+        });
+        Some(rect)
+    }
+
+    pub fn size(&self) -> Vector2<f32> {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> Vector2<f32> {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn contains(&self, point: Vector2<f32>) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+    }
+}
+
+// Ray is a half line in world space, the direction is normalized.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3<f32>,
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: Vector3<f32>, direction: Vector3<f32>) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    pub fn at(&self, distance: f32) -> Vector3<f32> {
+        self.origin + self.direction * distance
+    }
+
+    // point where the ray crosses the horizontal plane at the given z, if the
+    // ray goes towards it
+    pub fn intersect_plane_z(&self, z: f32) -> Option<Vector3<f32>> {
+        if self.direction.z.abs() < f32::EPSILON {
+            return None;
+        }
+        let distance = (z - self.origin.z) / self.direction.z;
+        if distance < 0.0 {
+            return None;
+        }
+        Some(self.at(distance))
+    }
+}
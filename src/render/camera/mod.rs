@@ -1,7 +1,68 @@
+mod cameras;
 mod controller;
+mod follow;
+mod geometry;
 mod ortho;
 mod perspective;
+mod pixel;
+mod scaling;
+mod shake;
 
-pub use self::controller::CameraController;
+use std::fmt::Debug;
+
+use cgmath::Matrix4;
+
+pub use self::cameras::{CameraId, CameraSettings, Cameras};
+pub use self::controller::{
+    CameraController, CAMERA_FLY_FORWARD, CAMERA_FLY_UP, CAMERA_LOOK, CAMERA_MOVE_X, CAMERA_MOVE_Y,
+    CAMERA_MOVE_Z, CAMERA_PAN, CAMERA_RESET_ZOOM, CAMERA_ZOOM,
+};
+pub use self::follow::CameraFollow;
+pub use self::geometry::{Ray, Rect};
 pub use self::ortho::CameraOrthographic;
 pub use self::perspective::CameraPerspective;
+pub use self::pixel::PixelPerfect;
+pub use self::scaling::{ScalingPolicy, ViewportRect};
+pub use self::shake::CameraShake;
+
+// Camera is a projection driven by a CameraController. Dimensions are the
+// size of the window, expressed in logical pixels.
+pub trait Camera: Debug {
+    fn projection_matrix(&self) -> Matrix4<f32>;
+    fn resize(&mut self, width: f32, height: f32);
+    fn zoom(&self) -> f32;
+    fn set_zoom(&mut self, amount: f32);
+    fn reset_zoom(&mut self);
+    fn set_scaling_policy(&mut self, policy: ScalingPolicy);
+    // area of the window the camera renders to
+    fn viewport(&self) -> ViewportRect;
+
+    // pixel perfect mode, only supported by the orthographic camera
+    fn set_pixel_perfect(&mut self, _pixel_perfect: Option<PixelPerfect>) {}
+    fn pixel_perfect(&self) -> Option<PixelPerfect> {
+        None
+    }
+    // pixel perfect cameras work in physical pixels
+    fn set_scale_factor(&mut self, _scale_factor: f64) {}
+    // size of the visible area in texels, for pixel perfect cameras
+    fn texels(&self) -> Option<[u32; 2]> {
+        None
+    }
+}
+
+// CameraProjection selects the camera used by the engine. The orthographic
+// camera is moved in 2D, the perspective camera flies through the world.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CameraProjection {
+    Orthographic,
+    Perspective,
+}
+
+impl CameraProjection {
+    pub fn create(self, width: f32, height: f32) -> Box<dyn Camera> {
+        match self {
+            CameraProjection::Orthographic => Box::new(CameraOrthographic::new(width, height)),
+            CameraProjection::Perspective => Box::new(CameraPerspective::new(width, height)),
+        }
+    }
+}
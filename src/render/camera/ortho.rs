@@ -1,12 +1,19 @@
 use cgmath::{Matrix4, SquareMatrix};
 
+use super::{Camera, PixelPerfect, ScalingPolicy, ViewportRect};
+
 #[derive(Debug, Copy, Clone)]
 pub struct CameraOrthographic {
-    width: u32,
-    height: u32,
+    width: f32,
+    height: f32,
     aspect_ratio: f32,
+    policy: ScalingPolicy,
+    // window height when the camera was created, see ScalingPolicy::Expand
+    reference_height: f32,
     zoom_base: f32,
     zoom: f32,
+    pixel_perfect: Option<PixelPerfect>,
+    scale_factor: f64,
     near: f32,
     far: f32,
     proj: Matrix4<f32>,
@@ -17,60 +24,141 @@ pub struct CameraOrthographic {
 // the clipping planes. These planes form a box with the minimum corner at
 // (left, bottom, -near) and the maximum corner at (right, top, -far).
 impl CameraOrthographic {
-    pub fn new(width: u32, height: u32) -> Self {
+    pub fn new(width: f32, height: f32) -> Self {
         let mut camera = Self { ..Self::default() };
         camera.resize(width, height);
         camera
     }
 
-    pub fn projection_matrix(&self) -> Matrix4<f32> {
-        self.proj
+    // texels visible and the area of the window they cover, in pixel perfect
+    // mode
+    fn pixel_layout(&self) -> ([u32; 2], ViewportRect) {
+        let scale_factor = self.scale_factor as f32;
+        PixelPerfect::layout(
+            self.width * scale_factor,
+            self.height * scale_factor,
+            PixelPerfect::scale_for_zoom(self.zoom),
+        )
     }
 
-    pub fn set_zoom(&mut self, amount: f32) {
-        self.zoom = amount;
-        if self.zoom < 0.1 {
-            self.zoom = 0.1;
+    fn compute_projection_matrix(&mut self) {
+        if let Some(pixel_perfect) = self.pixel_perfect {
+            // the center of the view falls on a texel corner even when the
+            // number of texels is odd, so that texels match whole pixels
+            let [width, height] = self.pixel_layout().0;
+            let ppu = pixel_perfect.pixels_per_unit;
+            let left = -((width / 2) as f32) / ppu;
+            let bottom = -((height / 2) as f32) / ppu;
+            self.proj = cgmath::ortho(
+                left,
+                left + width as f32 / ppu,
+                bottom,
+                bottom + height as f32 / ppu,
+                self.near,
+                self.far,
+            );
+            return;
         }
+        let half_height = self.policy.half_height(self.height, self.reference_height) * self.zoom;
+        self.proj = cgmath::ortho(
+            -self.aspect_ratio * half_height,
+            self.aspect_ratio * half_height,
+            -half_height,
+            half_height,
+            self.near,
+            self.far,
+        )
+    }
+}
+
+impl Camera for CameraOrthographic {
+    fn projection_matrix(&self) -> Matrix4<f32> {
+        self.proj
+    }
+
+    fn set_zoom(&mut self, amount: f32) {
+        self.zoom = match self.pixel_perfect {
+            // only whole scales avoid shimmering
+            Some(_) => PixelPerfect::zoom_for_scale(PixelPerfect::scale_for_zoom(amount)),
+            None => amount.max(0.1),
+        };
         self.compute_projection_matrix()
     }
 
-    pub fn zoom(&self) -> f32 {
+    fn zoom(&self) -> f32 {
         self.zoom
     }
 
-    pub fn reset_zoom(&mut self) {
+    fn reset_zoom(&mut self) {
         self.zoom = self.zoom_base;
         self.compute_projection_matrix()
     }
 
-    pub fn resize(&mut self, width: u32, height: u32) {
+    // width and height are expressed in logical pixels
+    fn resize(&mut self, width: f32, height: f32) {
+        // a minimized window reports a size of zero
+        if width == 0.0 || height == 0.0 {
+            return;
+        }
+        if self.reference_height == 0.0 {
+            self.reference_height = height;
+        }
         self.width = width;
         self.height = height;
-        self.aspect_ratio = width as f32 / height as f32;
+        self.aspect_ratio = self.policy.aspect_ratio(width, height);
         self.compute_projection_matrix()
     }
 
-    fn compute_projection_matrix(&mut self) {
-        self.proj = cgmath::ortho(
-            -self.aspect_ratio * self.zoom,
-            self.aspect_ratio * self.zoom,
-            -self.zoom,
-            self.zoom,
-            self.near,
-            self.far,
-        )
+    fn set_scaling_policy(&mut self, policy: ScalingPolicy) {
+        self.policy = policy;
+        self.aspect_ratio = policy.aspect_ratio(self.width, self.height);
+        self.compute_projection_matrix()
+    }
+
+    fn viewport(&self) -> ViewportRect {
+        match self.pixel_perfect {
+            Some(_) => self.pixel_layout().1,
+            None => self.policy.viewport(self.width, self.height),
+        }
+    }
+
+    fn set_pixel_perfect(&mut self, pixel_perfect: Option<PixelPerfect>) {
+        self.pixel_perfect = pixel_perfect;
+        self.zoom_base = match pixel_perfect {
+            Some(pixel_perfect) => PixelPerfect::zoom_for_scale(pixel_perfect.scale),
+            None => 1.0,
+        };
+        self.reset_zoom()
+    }
+
+    fn pixel_perfect(&self) -> Option<PixelPerfect> {
+        self.pixel_perfect
+    }
+
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        if self.scale_factor != scale_factor {
+            self.scale_factor = scale_factor;
+            self.compute_projection_matrix()
+        }
+    }
+
+    fn texels(&self) -> Option<[u32; 2]> {
+        self.pixel_perfect.map(|_| self.pixel_layout().0)
     }
 }
 
 impl Default for CameraOrthographic {
     fn default() -> Self {
         Self {
-            width: 0,
-            height: 0,
+            width: 0.0,
+            height: 0.0,
             aspect_ratio: 0.0,
+            policy: ScalingPolicy::default(),
+            reference_height: 0.0,
             zoom_base: 1.0,
             zoom: 1.0,
+            pixel_perfect: None,
+            scale_factor: 1.0,
             near: 0.1,
             far: 10.0,
             proj: Matrix4::identity(),
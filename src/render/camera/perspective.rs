@@ -1,53 +1,89 @@
-#![allow(dead_code)]
 use cgmath::{Deg, Matrix4, SquareMatrix};
 
+use super::{Camera, ScalingPolicy, ViewportRect};
+
 #[derive(Debug, Copy, Clone)]
 pub struct CameraPerspective {
-    width: usize,
-    height: usize,
+    width: f32,
+    height: f32,
     aspect_ratio: f32,
+    // only the viewport of the policy applies, the visible area depends on
+    // the field of view
+    policy: ScalingPolicy,
     fov: Deg<f32>,
+    // the zoom scales the field of view
+    zoom: f32,
     near: f32,
     far: f32,
     proj: Matrix4<f32>,
 }
 
 impl CameraPerspective {
-    pub fn new(width: usize, height: usize) -> Self {
+    pub fn new(width: f32, height: f32) -> Self {
         let mut camera = Self { ..Self::default() };
         camera.resize(width, height);
         camera
     }
 
-    pub fn dimensions(&self) -> (usize, usize) {
-        (self.width, self.height)
+    fn compute_projection_matrix(&mut self) {
+        let fov = Deg((self.fov.0 * self.zoom).clamp(10.0, 150.0));
+        self.proj = cgmath::perspective(fov, self.aspect_ratio, self.near, self.far)
     }
+}
 
-    pub fn projection_matrix(&self) -> Matrix4<f32> {
+impl Camera for CameraPerspective {
+    fn projection_matrix(&self) -> Matrix4<f32> {
         self.proj
     }
 
-    pub fn resize(&mut self, width: usize, height: usize) {
+    // width and height are expressed in logical pixels
+    fn resize(&mut self, width: f32, height: f32) {
+        // a minimized window reports a size of zero
+        if width == 0.0 || height == 0.0 {
+            return;
+        }
         self.width = width;
         self.height = height;
-        self.aspect_ratio = width as f32 / height as f32;
+        self.aspect_ratio = self.policy.aspect_ratio(width, height);
         self.compute_projection_matrix()
     }
 
-    fn compute_projection_matrix(&mut self) {
-        self.proj = cgmath::perspective(self.fov, self.aspect_ratio, self.near, self.far)
+    fn set_scaling_policy(&mut self, policy: ScalingPolicy) {
+        self.policy = policy;
+        self.aspect_ratio = policy.aspect_ratio(self.width, self.height);
+        self.compute_projection_matrix()
+    }
+
+    fn viewport(&self) -> ViewportRect {
+        self.policy.viewport(self.width, self.height)
+    }
+
+    fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    fn set_zoom(&mut self, amount: f32) {
+        self.zoom = amount.max(0.1);
+        self.compute_projection_matrix()
+    }
+
+    fn reset_zoom(&mut self) {
+        self.zoom = 1.0;
+        self.compute_projection_matrix()
     }
 }
 
 impl Default for CameraPerspective {
     fn default() -> Self {
         Self {
-            width: 0,
-            height: 0,
+            width: 0.0,
+            height: 0.0,
             aspect_ratio: 0.0,
+            policy: ScalingPolicy::default(),
             fov: Deg(60.0f32),
+            zoom: 1.0,
             near: 0.1,
-            far: 10.0,
+            far: 100.0,
             proj: Matrix4::identity(),
         }
     }
@@ -0,0 +1,97 @@
+use super::ViewportRect;
+
+// largest number of physical pixels per texel
+const MAX_SCALE: u32 = 16;
+
+// PixelPerfect draws pixel art without shimmering: the orthographic camera
+// shows a whole number of texels, each covering the same whole number of
+// physical pixels, and the view is snapped to whole texels.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PixelPerfect {
+    // texels of the art per world unit
+    pub pixels_per_unit: f32,
+    // physical pixels per texel when the zoom is reset
+    pub scale: u32,
+    // draw at the resolution of the art and upscale the result, so that
+    // everything drawn lands on the texel grid
+    pub low_resolution: bool,
+}
+
+impl PixelPerfect {
+    pub fn new(pixels_per_unit: f32) -> Self {
+        Self {
+            pixels_per_unit,
+            scale: 1,
+            low_resolution: false,
+        }
+    }
+
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale.clamp(1, MAX_SCALE);
+        self
+    }
+
+    pub fn with_low_resolution(mut self, b: bool) -> Self {
+        self.low_resolution = b;
+        self
+    }
+
+    // closest position on the texel grid
+    pub fn snap(&self, value: f32) -> f32 {
+        (value * self.pixels_per_unit).round() / self.pixels_per_unit
+    }
+
+    // The zoom of a pixel perfect camera is 1 / scale, only whole scales are
+    // allowed.
+    pub fn scale_for_zoom(zoom: f32) -> u32 {
+        if zoom <= 0.0 {
+            return MAX_SCALE;
+        }
+        (1.0 / zoom).round().clamp(1.0, MAX_SCALE as f32) as u32
+    }
+
+    pub fn zoom_for_scale(scale: u32) -> f32 {
+        1.0 / scale as f32
+    }
+
+    // texels visible in a window of the given size in physical pixels, and the
+    // area of the window they cover: a whole number of pixels, centered
+    pub fn layout(width: f32, height: f32, scale: u32) -> ([u32; 2], ViewportRect) {
+        if width < 1.0 || height < 1.0 {
+            return ([1, 1], ViewportRect::FULL);
+        }
+        let scale = scale.max(1);
+        let texels = [width, height].map(|d| ((d.floor() as u32) / scale).max(1));
+        let [covered_width, covered_height] = texels.map(|t| (t * scale) as f32);
+        let viewport = ViewportRect {
+            x: ((width - covered_width) / 2.0).floor().max(0.0) / width,
+            y: ((height - covered_height) / 2.0).floor().max(0.0) / height,
+            width: covered_width.min(width) / width,
+            height: covered_height.min(height) / height,
+        };
+        (texels, viewport)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_texels_and_pixels() {
+        assert_eq!(PixelPerfect::scale_for_zoom(1.0), 1);
+        assert_eq!(PixelPerfect::scale_for_zoom(0.45), 2);
+        assert_eq!(PixelPerfect::scale_for_zoom(0.001), MAX_SCALE);
+
+        let (texels, viewport) = PixelPerfect::layout(1001.0, 600.0, 3);
+        assert_eq!(texels, [333, 200]);
+        // 2 pixels left over horizontally, one on each side
+        assert_eq!(viewport.x * 1001.0, 1.0);
+        assert_eq!(viewport.width * 1001.0, 999.0);
+        assert_eq!(viewport.height, 1.0);
+
+        let pixel_perfect = PixelPerfect::new(16.0);
+        assert_eq!(pixel_perfect.snap(1.02), 1.0);
+        assert_eq!(pixel_perfect.snap(1.04), 1.0625);
+    }
+}
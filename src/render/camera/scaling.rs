@@ -0,0 +1,146 @@
+// ViewportRect is the area of the window the camera renders to, expressed as
+// fractions of the window size with the origin at the top-left corner.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ViewportRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ViewportRect {
+    pub const FULL: ViewportRect = ViewportRect {
+        x: 0.0,
+        y: 0.0,
+        width: 1.0,
+        height: 1.0,
+    };
+
+    // largest centered area with the given aspect ratio, the rest of the
+    // window is left to letterbox (top and bottom) or pillarbox (left and
+    // right) bars
+    pub fn fit(aspect_ratio: f32, window_width: f32, window_height: f32) -> Self {
+        let window_aspect_ratio = window_width / window_height;
+        if window_aspect_ratio > aspect_ratio {
+            let width = aspect_ratio / window_aspect_ratio;
+            ViewportRect {
+                x: (1.0 - width) / 2.0,
+                y: 0.0,
+                width,
+                height: 1.0,
+            }
+        } else {
+            let height = window_aspect_ratio / aspect_ratio;
+            ViewportRect {
+                x: 0.0,
+                y: (1.0 - height) / 2.0,
+                width: 1.0,
+                height,
+            }
+        }
+    }
+
+    // the same area expressed as fractions of another area, clipped to it
+    pub fn relative_to(&self, area: &ViewportRect) -> ViewportRect {
+        if area.width <= 0.0 || area.height <= 0.0 {
+            return *self;
+        }
+        let x0 = ((self.x - area.x) / area.width).clamp(0.0, 1.0);
+        let y0 = ((self.y - area.y) / area.height).clamp(0.0, 1.0);
+        let x1 = ((self.x + self.width - area.x) / area.width).clamp(0.0, 1.0);
+        let y1 = ((self.y + self.height - area.y) / area.height).clamp(0.0, 1.0);
+        ViewportRect {
+            x: x0,
+            y: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+        }
+    }
+}
+
+// ScalingPolicy decides what part of the world is visible when the window is
+// resized.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum ScalingPolicy {
+    // the world keeps the same size on screen, a bigger window shows more of it
+    Expand,
+    // the visible height stays the same, the width follows the window
+    #[default]
+    FixedHeight,
+    // the visible area keeps the given aspect ratio, with bars around it
+    Fit {
+        aspect_ratio: f32,
+    },
+    // the visible area is width x height world units, with bars around it
+    FixedResolution {
+        width: f32,
+        height: f32,
+    },
+}
+
+impl ScalingPolicy {
+    pub fn viewport(&self, window_width: f32, window_height: f32) -> ViewportRect {
+        if window_width == 0.0 || window_height == 0.0 {
+            return ViewportRect::FULL;
+        }
+        match *self {
+            ScalingPolicy::Expand | ScalingPolicy::FixedHeight => ViewportRect::FULL,
+            ScalingPolicy::Fit { aspect_ratio } => {
+                ViewportRect::fit(aspect_ratio, window_width, window_height)
+            }
+            ScalingPolicy::FixedResolution { width, height } => {
+                ViewportRect::fit(width / height, window_width, window_height)
+            }
+        }
+    }
+
+    // aspect ratio of the viewport in a window of the given size
+    pub fn aspect_ratio(&self, window_width: f32, window_height: f32) -> f32 {
+        if window_height == 0.0 {
+            return 0.0;
+        }
+        let viewport = self.viewport(window_width, window_height);
+        (viewport.width * window_width) / (viewport.height * window_height)
+    }
+
+    // half of the visible height at zoom 1.0, reference_height is the height
+    // of the window when the camera was created
+    pub fn half_height(&self, window_height: f32, reference_height: f32) -> f32 {
+        match *self {
+            ScalingPolicy::Expand if reference_height > 0.0 => window_height / reference_height,
+            ScalingPolicy::Expand => 1.0,
+            ScalingPolicy::FixedHeight | ScalingPolicy::Fit { .. } => 1.0,
+            ScalingPolicy::FixedResolution { height, .. } => height / 2.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_adds_bars() {
+        // wider window: pillarbox
+        let viewport = ScalingPolicy::Fit { aspect_ratio: 1.0 }.viewport(200.0, 100.0);
+        assert_eq!(
+            viewport,
+            ViewportRect {
+                x: 0.25,
+                y: 0.0,
+                width: 0.5,
+                height: 1.0
+            }
+        );
+
+        // taller window: letterbox
+        let viewport = ScalingPolicy::FixedResolution {
+            width: 320.0,
+            height: 180.0,
+        }
+        .viewport(1280.0, 1440.0);
+        assert_eq!(viewport.x, 0.0);
+        assert_eq!(viewport.height, 0.5);
+        assert_eq!(viewport.y, 0.25);
+    }
+}
@@ -0,0 +1,168 @@
+use cgmath::{Matrix4, Rad, SquareMatrix, Vector2, Vector3};
+
+// seeds of the noise driving each offset, so that they move independently
+const SEED_X: u32 = 0;
+const SEED_Y: u32 = 1;
+const SEED_ROTATION: u32 = 2;
+const SEED_ZOOM: u32 = 3;
+
+// CameraShake shakes the camera based on trauma: events add trauma, which
+// decays over time, and the shake is proportional to the square of the
+// trauma so that small amounts barely show.
+//
+// ref: Math for Game Programmers: Juicing Your Cameras With Math (GDC 2016)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CameraShake {
+    trauma: f32,
+    time: f32,
+    // maximum translation, in world units
+    max_offset: f32,
+    // maximum rotation around the view direction, in radians
+    max_angle: f32,
+    // maximum zoom, as a fraction of the current zoom
+    max_zoom: f32,
+    // speed of the shake, in noise periods per second
+    frequency: f32,
+    // trauma removed per second
+    decay: f32,
+}
+
+impl CameraShake {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_offset(mut self, offset: f32) -> Self {
+        self.max_offset = offset;
+        self
+    }
+
+    pub fn with_max_angle(mut self, angle: f32) -> Self {
+        self.max_angle = angle;
+        self
+    }
+
+    pub fn with_max_zoom(mut self, zoom: f32) -> Self {
+        self.max_zoom = zoom;
+        self
+    }
+
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    pub fn with_decay(mut self, decay: f32) -> Self {
+        self.decay = decay;
+        self
+    }
+
+    // add trauma, the total is kept between 0.0 and 1.0
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    // keep the trauma when changing the settings
+    pub fn configure(&mut self, settings: CameraShake) {
+        *self = CameraShake {
+            trauma: self.trauma,
+            time: self.time,
+            ..settings
+        };
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
+        self.trauma = (self.trauma - self.decay * dt).max(0.0);
+    }
+
+    // transform applied on top of the view matrix
+    pub fn view_offset(&self) -> Matrix4<f32> {
+        let shake = self.trauma * self.trauma;
+        if shake == 0.0 {
+            return Matrix4::identity();
+        }
+        let t = self.time * self.frequency;
+        let translation =
+            Vector2::new(noise(SEED_X, t), noise(SEED_Y, t)) * self.max_offset * shake;
+        let angle = noise(SEED_ROTATION, t) * self.max_angle * shake;
+        let zoom = 1.0 + noise(SEED_ZOOM, t) * self.max_zoom * shake;
+
+        Matrix4::from_nonuniform_scale(zoom, zoom, 1.0)
+            * Matrix4::from_angle_z(Rad(angle))
+            * Matrix4::from_translation(Vector3::new(-translation.x, -translation.y, 0.0))
+    }
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self {
+            trauma: 0.0,
+            time: 0.0,
+            max_offset: 0.1,
+            max_angle: 0.1,
+            max_zoom: 0.1,
+            frequency: 15.0,
+            decay: 1.0,
+        }
+    }
+}
+
+// smooth 1D gradient noise between -1.0 and 1.0
+fn noise(seed: u32, x: f32) -> f32 {
+    let i = x.floor();
+    let f = x - i;
+    let i = i as i32;
+    let a = gradient(seed, i) * f;
+    let b = gradient(seed, i.wrapping_add(1)) * (f - 1.0);
+    let t = f * f * (3.0 - 2.0 * f);
+    // gradient noise stays within -0.5 and 0.5
+    ((a + (b - a) * t) * 2.0).clamp(-1.0, 1.0)
+}
+
+// pseudo random gradient between -1.0 and 1.0 for a lattice point
+fn gradient(seed: u32, i: i32) -> f32 {
+    let mut h = (i as u32).wrapping_mul(0x9e37_79b9) ^ seed.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846c_a68b);
+    h ^= h >> 16;
+    (h as f32 / u32::MAX as f32) * 2.0 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trauma_decays() {
+        let mut shake = CameraShake::new().with_decay(0.5);
+        shake.add_trauma(0.6);
+        shake.add_trauma(0.6);
+        assert_eq!(shake.trauma(), 1.0);
+
+        shake.update(0.05);
+        assert_ne!(shake.view_offset(), Matrix4::identity());
+        shake.update(0.95);
+        assert_eq!(shake.trauma(), 0.5);
+        shake.update(1.5);
+        assert_eq!(shake.trauma(), 0.0);
+        assert_eq!(shake.view_offset(), Matrix4::identity());
+    }
+
+    #[test]
+    fn noise_is_smooth_and_bounded() {
+        let mut previous = noise(0, 0.0);
+        for i in 1..1000 {
+            let value = noise(0, i as f32 * 0.01);
+            assert!((-1.0..=1.0).contains(&value));
+            assert!((value - previous).abs() < 0.1);
+            previous = value;
+        }
+    }
+}
@@ -274,7 +274,13 @@ fn create_swapchain<'a>(
             min_image_count: image_count, // How many buffers to use in the swapchain
             image_format,
             image_extent: dimensions.into(),
-            image_usage: ImageUsage::color_attachment(), // What the images are going to be used for
+            // What the images are going to be used for, low resolution
+            // frames are blitted to them
+            image_usage: ImageUsage {
+                color_attachment: true,
+                transfer_dst: true,
+                ..ImageUsage::none()
+            },
             composite_alpha,
             ..Default::default()
         },
@@ -1,7 +1,16 @@
+mod blend;
 pub mod camera;
 mod device;
 mod quad;
 mod renderer;
+mod sprite;
+mod transform;
 
+pub use self::blend::BlendMode;
 pub use self::device::{Device, DeviceDefinition};
-pub use self::renderer::Renderer2D;
+pub use self::quad::TextureHandle;
+pub use self::renderer::{
+    LowResolutionTarget, RenderStats, RenderView, Renderer2D, VULKAN_COORD_MAGIC_PROJ,
+};
+pub use self::sprite::{AnimatedSprite, AnimationMode, SpriteSheet, UvRect};
+pub use self::transform::Transform2D;
@@ -1,4 +1,5 @@
 mod pipeline;
 mod renderpass;
 
+pub use self::pipeline::TextureHandle;
 pub use self::renderpass::QuadRenderPass;
@@ -1,11 +1,11 @@
-use std::{error::Error, fmt::Debug, io::Cursor, result, sync::Arc};
+use std::{error::Error, fmt::Debug, io::Cursor, ops::Range, result, sync::Arc};
 
 use bytemuck::{Pod, Zeroable};
-use cgmath::{Matrix4, Vector2, Vector4};
+use cgmath::{Matrix4, SquareMatrix, Vector2, Vector3, Vector4};
 use vulkano::{
-    buffer::{BufferUsage, CpuBufferPool, DeviceLocalBuffer, ImmutableBuffer},
+    buffer::{BufferUsage, CpuBufferPool, ImmutableBuffer},
     command_buffer::{
-        AutoCommandBufferBuilder, CommandBufferExecFuture, CommandBufferUsage, CopyBufferInfo,
+        AutoCommandBufferBuilder, CommandBufferExecFuture, CommandBufferUsage,
         PrimaryAutoCommandBuffer, SecondaryAutoCommandBuffer,
     },
     descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
@@ -14,17 +14,22 @@ use vulkano::{
     image::{view::ImageView, ImageDimensions, ImmutableImage, MipmapsCount},
     pipeline::{
         graphics::{
+            color_blend::{
+                AttachmentBlend, BlendFactor, BlendOp, ColorBlendState, ColorComponents,
+            },
+            depth_stencil::{CompareOp, DepthState, DepthStencilState},
             input_assembly::InputAssemblyState,
             vertex_input::BuffersDefinition,
-            viewport::{Viewport, ViewportState},
+            viewport::{Scissor, Viewport, ViewportState},
         },
-        GraphicsPipeline, Pipeline,
+        GraphicsPipeline, Pipeline, StateMode,
     },
     render_pass::Subpass,
-    sampler::{Sampler, SamplerCreateInfo},
+    sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
     sync::{self, GpuFuture, NowFuture},
 };
 
+use crate::render::{BlendMode, RenderStats, RenderView, Transform2D, UvRect};
 use crate::TIME;
 
 type Result<T> = result::Result<T, Box<dyn Error>>;
@@ -54,20 +59,46 @@ struct QuadVertexInstance {
     offset: [f32; 2],
     scale: [f32; 2],
     color: [f32; 4],
+    // area of the texture drawn
+    uv_offset: [f32; 2],
+    uv_scale: [f32; 2],
+    // counter-clockwise, in radians, around the pivot
+    rotation: f32,
+    pivot: [f32; 2],
+    z: f32,
+    // value written to the depth buffer, see draw_depths
+    depth: f32,
 }
-vulkano::impl_vertex!(QuadVertexInstance, offset, scale, color);
+vulkano::impl_vertex!(
+    QuadVertexInstance,
+    offset,
+    scale,
+    color,
+    uv_offset,
+    uv_scale,
+    rotation,
+    pivot,
+    z,
+    depth
+);
 
 impl QuadVertexInstance {
-    fn new(off: &[f32; 2], scale: &[f32; 2], col: &[f32; 4]) -> Self {
+    fn new(transform: &Transform2D, col: &[f32; 4], uv: &UvRect) -> Self {
         QuadVertexInstance {
-            offset: *off,
-            scale: *scale,
+            offset: transform.position.into(),
+            scale: transform.size.into(),
             color: *col,
+            uv_offset: [uv.x, uv.y],
+            uv_scale: [uv.width, uv.height],
+            rotation: transform.rotation,
+            pivot: transform.pivot.into(),
+            z: transform.z,
+            depth: CLEAR_DEPTH,
         }
     }
 }
 
-// NOTE: Vulkan 0.0 is top-left corner
+// NOTE: Vulkan 0.0 is top-left corner, so is the texture origin
 //
 // 0 +--------------+ 1
 //   |              |
@@ -85,90 +116,122 @@ const QUAD_VERTICES: [Vector2<f32>; 4] = [
 const QUAD_TEX_COORDS: [Vector2<f32>; 4] = [
     Vector2::new(0.0, 0.0),
     Vector2::new(1.0, 0.0),
-    Vector2::new(0.0, 1.0),
     Vector2::new(1.0, 1.0),
+    Vector2::new(0.0, 1.0),
 ];
 
 const DEFAULT_MAX_QUADS: usize = 10000;
 
-/// contains a batch of quads data that can be used in a single draw call.
-struct QuadBufferData {
-    quads_count: usize,
-    vertex_buffer: Arc<ImmutableBuffer<[QuadVertex]>>,
-    instance_buffer: Arc<ImmutableBuffer<[QuadVertexInstance]>>,
-    future: Box<dyn GpuFuture>,
+// TextureHandle identifies a texture loaded by the renderer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TextureHandle(usize);
+
+impl TextureHandle {
+    // 1x1 white texture, used by untextured quads
+    pub const WHITE: TextureHandle = TextureHandle(0);
 }
 
-impl Debug for QuadBufferData {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "QuadBufferData{{quads_count: {}}}", self.quads_count)
-    }
+// what the pipeline needs to know about a quad besides its instance data
+#[derive(Debug, Copy, Clone)]
+struct QuadInfo {
+    // layer bit
+    layer: u32,
+    texture: TextureHandle,
+    // corners of the quad, at its z
+    corners: [Vector3<f32>; 4],
+    z: f32,
+    blend: BlendMode,
+    // opaque quads are drawn with depth testing in any order, the others are
+    // sorted back to front
+    opaque: bool,
+}
+
+struct Texture {
+    view: Arc<ImageView<ImmutableImage>>,
+    // no pixel is translucent
+    opaque: bool,
 }
 
+// depth of the quads clearing a viewport, the far end of the depth range
+const CLEAR_DEPTH: f32 = 1.0;
+
 pub struct QuadPipeline {
     // graphics pipeline
     gfx_queue: Arc<Queue>,
+    // opaque quads write depth, translucent quads only test it and are
+    // blended, the clear pipelines overwrite the viewport whatever its depth
     pipeline: Arc<GraphicsPipeline>,
-    // batch rendering
+    // indexed by blend mode
+    transparent_pipelines: Vec<Arc<GraphicsPipeline>>,
+    clear_pipeline: Arc<GraphicsPipeline>,
+    clear_depth_pipeline: Arc<GraphicsPipeline>,
+    // batch rendering: the quads of the frame are kept until they are drawn
+    // by every camera, max_quads is the number of quads per draw call
     max_quads: usize,
-    quads_count: usize,
-    vertices: Vec<QuadVertex>,
+    vertex_buffer: Arc<ImmutableBuffer<[QuadVertex]>>,
     instances: Vec<QuadVertexInstance>,
-    buffer_data: Vec<QuadBufferData>,
-    uniform_buffer: Arc<CpuBufferPool<vs::ty::UniformBufferObject>>,
-    uniform_buffer_dev: Arc<DeviceLocalBuffer<vs::ty::UniformBufferObject>>,
-    uniform_descriptor_set: Arc<PersistentDescriptorSet>,
+    quads: Vec<QuadInfo>,
+    culling: bool,
+    stats: RenderStats,
+    // one mvp per camera and per frame
+    uniform_buffer: CpuBufferPool<vs::ty::UniformBufferObject>,
+    // indexed by TextureHandle
+    textures: Vec<Texture>,
+    sampler: Arc<Sampler>,
+}
+
+impl Debug for QuadPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "QuadPipeline{{quads_count: {}}}", self.instances.len())
+    }
 }
 
 impl QuadPipeline {
     // TODO: subpass == Subpass::from(render_pass.clone(), 0).unwrap()
     pub fn new(gfx_queue: Arc<Queue>, subpass: Subpass) -> Self {
         let max_quads = DEFAULT_MAX_QUADS;
-        // graphics pipeline
-        let pipeline = {
-            // compile shaders
-            let vs = vs::load(gfx_queue.device().clone())
-                .expect("failed to create vertex shader module");
-            let fs = fs::load(gfx_queue.device().clone())
-                .expect("failed to create fragment shader module");
-            // create graphics pipeline
-            GraphicsPipeline::start()
-                .vertex_input_state(
-                    BuffersDefinition::new()
-                        .vertex::<QuadVertex>()
-                        .instance::<QuadVertexInstance>(),
+        // graphics pipelines
+        let pipeline = build_pipeline(
+            &gfx_queue,
+            subpass.clone(),
+            depth_state(true, CompareOp::LessOrEqual),
+            ColorBlendState::new(1),
+        );
+        let transparent_pipelines = BlendMode::ALL
+            .iter()
+            .map(|blend| {
+                build_pipeline(
+                    &gfx_queue,
+                    subpass.clone(),
+                    depth_state(false, CompareOp::LessOrEqual),
+                    ColorBlendState::new(1).blend(attachment_blend(*blend)),
                 )
-                .vertex_shader(vs.entry_point("main").unwrap(), ())
-                .input_assembly_state(InputAssemblyState::new())
-                .fragment_shader(fs.entry_point("main").unwrap(), ())
-                .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
-                .render_pass(subpass)
-                .build(gfx_queue.device().clone())
-                .unwrap()
+            })
+            .collect();
+        let clear_pipeline = build_pipeline(
+            &gfx_queue,
+            subpass.clone(),
+            depth_state(true, CompareOp::Always),
+            ColorBlendState::new(1),
+        );
+        let clear_depth_pipeline = {
+            let mut color_blend_state = ColorBlendState::new(1);
+            color_blend_state.attachments[0].color_write_mask = ColorComponents::none();
+            build_pipeline(
+                &gfx_queue,
+                subpass,
+                depth_state(true, CompareOp::Always),
+                color_blend_state,
+            )
         };
 
-        // TODO: textures
-        //
-        // 1. load image from file
-        // 2. upload image data to GPU
-        // 3. create sampler
-        // 4. write sampler and texture to descriptor set
-
-        // create white texture
+        // create white texture, every texture is bound on its own so that
+        // they can have different sizes
         let (white_texture, white_texture_future) = {
             let dimensions = ImageDimensions::Dim2d {
                 width: 1,
                 height: 1,
-                // set `array_layers: 2` even if we only have one so that we can
-                // use sampler2DArray in the shader.
-                //
-                // TODO: using a sampler2DArray requires that all textures
-                //       be of the SAME SIZE!
-                //
-                // Explanation: ImageView::new_default(image) calls
-                // ImageViewCreateInfo::from_image(&image) and from_image() chooses
-                // a viewType Dim2dArray only if array-laters > 1
-                array_layers: 2,
+                array_layers: 1,
             };
             let image_data = (0..dimensions.width() * dimensions.height() * 4).map(|_| WHITE);
             let (image, future) = ImmutableImage::from_iter(
@@ -186,77 +249,128 @@ impl QuadPipeline {
         };
         white_texture_future.flush().expect("white texture flush");
 
-        let white_sampler = Sampler::new(
+        // nearest filtering keeps pixel art sharp, clamping keeps the frames of
+        // a sprite sheet from bleeding into each other
+        let sampler = Sampler::new(
             gfx_queue.device().clone(),
-            SamplerCreateInfo::simple_repeat_linear(),
+            SamplerCreateInfo {
+                mag_filter: Filter::Nearest,
+                min_filter: Filter::Nearest,
+                address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                ..Default::default()
+            },
         )
         .expect("create sampler");
 
-        // create cpu and gpu buffers (we will copy data between them each frame)
-        let uniform_buffer = Arc::new(CpuBufferPool::<vs::ty::UniformBufferObject>::new(
-            gfx_queue.device().clone(),
-            BufferUsage::transfer_src(),
-        ));
-        let uniform_buffer_dev = DeviceLocalBuffer::<vs::ty::UniformBufferObject>::new(
-            gfx_queue.device().clone(),
-            BufferUsage::uniform_buffer_transfer_dst(),
-            [gfx_queue.family()],
+        // the vertices are the same for every quad, only the instances change
+        let (vertex_buffer, vertex_buffer_future) = ImmutableBuffer::from_iter(
+            QUAD_INDICES.into_iter().map(|idx| {
+                QuadVertex::new(
+                    &QUAD_VERTICES[idx as usize].into(),
+                    &QUAD_TEX_COORDS[idx as usize].into(),
+                )
+            }),
+            BufferUsage::vertex_buffer_transfer_dst(),
+            gfx_queue.clone(),
         )
-        .expect("create device local buffer for storing mvp uniform");
+        .expect("create quad vertex buffer");
+        vertex_buffer_future
+            .flush()
+            .expect("quad vertex buffer flush");
 
-        // create descriptor set
-        let layout = pipeline.layout().set_layouts().get(0).unwrap();
-        let uniform_descriptor_set = PersistentDescriptorSet::new(
-            layout.clone(),
-            [
-                WriteDescriptorSet::buffer(0, uniform_buffer_dev.clone()),
-                WriteDescriptorSet::image_view_sampler(1, white_texture, white_sampler),
-            ],
-        )
-        .expect("create descriptor set for mvp uniform buffer");
+        let uniform_buffer = CpuBufferPool::<vs::ty::UniformBufferObject>::new(
+            gfx_queue.device().clone(),
+            BufferUsage::uniform_buffer(),
+        );
 
         Self {
             gfx_queue,
             pipeline,
+            transparent_pipelines,
+            clear_pipeline,
+            clear_depth_pipeline,
             max_quads,
-            quads_count: 0,
-            vertices: Vec::with_capacity(QUAD_INDICES.len()),
+            vertex_buffer,
             instances: Vec::with_capacity(max_quads),
-            buffer_data: Vec::new(),
+            quads: Vec::with_capacity(max_quads),
+            culling: true,
+            stats: RenderStats::default(),
             uniform_buffer,
-            uniform_buffer_dev,
-            uniform_descriptor_set,
+            textures: vec![Texture {
+                view: white_texture,
+                opaque: true,
+            }],
+            sampler,
         }
     }
 
-    pub fn add_quad(&mut self, position: Vector2<f32>, size: Vector2<f32>, color: Vector4<f32>) {
-        if self.quads_count >= self.max_quads {
-            // we must flush our current vertices/indices
-            self.flush_batch();
-        }
+    // decode a PNG image and upload it to the GPU
+    pub fn load_texture(&mut self, png_bytes: Vec<u8>) -> Result<TextureHandle> {
+        let (image_data, dimensions) = load_image_png(png_bytes)?;
+        let opaque = image_data.chunks_exact(4).all(|p| p[3] == u8::MAX);
+        let (view, future) = self.upload_texture(image_data, dimensions)?;
+        future.flush()?;
 
-        // add instance data
-        self.instances.push(QuadVertexInstance::new(
-            &position.into(),
-            &size.into(),
-            &color.into(),
-        ));
+        self.textures.push(Texture { view, opaque });
+        Ok(TextureHandle(self.textures.len() - 1))
+    }
+
+    // layers go from 0 to 31, quads on other layers are never drawn
+    pub fn add_quad(
+        &mut self,
+        transform: Transform2D,
+        color: Vector4<f32>,
+        uv: UvRect,
+        texture: TextureHandle,
+        blend: BlendMode,
+    ) {
+        // unknown textures are drawn white
+        let texture = if texture.0 < self.textures.len() {
+            texture
+        } else {
+            TextureHandle::WHITE
+        };
+        self.instances
+            .push(QuadVertexInstance::new(&transform, &color.into(), &uv));
+        self.quads.push(QuadInfo {
+            layer: 1u32.checked_shl(transform.layer).unwrap_or(0),
+            texture,
+            corners: transform.corners().map(|corner| corner.extend(transform.z)),
+            z: transform.z,
+            blend,
+            opaque: blend.keeps_opaque() && color.w >= 1.0 && self.textures[texture.0].opaque,
+        });
+    }
+
+    pub fn set_culling(&mut self, b: bool) {
+        self.culling = b;
+    }
 
-        // bump quad count
-        self.quads_count += 1;
+    // stats of the last draw
+    pub fn stats(&self) -> RenderStats {
+        self.stats
     }
 
+    // draw the quads of the frame once per view, in the order of the views
     pub fn draw(
         &mut self,
-        viewport_dimensions: [u32; 2],
+        framebuffer_dimensions: [u32; 2],
+        views: &[RenderView],
     ) -> Option<(SecondaryAutoCommandBuffer, Box<dyn GpuFuture>)> {
         TIME!("pipeline.draw");
 
-        // flush remaining quads
-        self.flush_batch();
+        let mut instances = std::mem::take(&mut self.instances);
+        let quads = std::mem::take(&mut self.quads);
+        for (instance, depth) in instances.iter_mut().zip(draw_depths(&quads)) {
+            instance.depth = depth;
+        }
+        self.stats = RenderStats {
+            quads: instances.len(),
+            ..RenderStats::default()
+        };
 
         // bail out if nothing to draw
-        if self.buffer_data.is_empty() {
+        if instances.is_empty() && views.iter().all(|v| v.clear_color.is_none()) {
             return None;
         }
 
@@ -268,123 +382,171 @@ impl QuadPipeline {
             self.pipeline.subpass().clone(),
         )
         .unwrap();
-        builder.set_viewport(
-            0,
-            [Viewport {
-                origin: [0.0, 0.0],
-                dimensions: [viewport_dimensions[0] as f32, viewport_dimensions[1] as f32],
-                depth_range: 0.0..1.0,
-            }],
-        );
 
         // create initial future used to chain buffer data futures
-        //
-        // TODO: maybe we could store a `previous_future` on the pipeline
-        //       used to store a future of all the init data. It could
-        //       then be retrieved so that we dont need to call
-        //       future.flush() in new()?
         let mut future = sync::now(self.gfx_queue.device().clone()).boxed();
 
-        // record draw commands on the secondary command buffer
-        if !self.buffer_data.is_empty() {
-            // bind pipeline
-            builder.bind_pipeline_graphics(self.pipeline.clone());
-
-            // bind uniform buffer descriptor set
-            builder.bind_descriptor_sets(
-                vulkano::pipeline::PipelineBindPoint::Graphics,
-                self.pipeline.layout().clone(),
+        let [width, height] = framebuffer_dimensions.map(|d| d as f32);
+        for view in views {
+            // the viewport is a fraction of the framebuffer, the scissor
+            // discards anything drawn outside of it (letterbox and pillarbox
+            // bars, other cameras)
+            let viewport = view.viewport;
+            let origin = [viewport.x * width, viewport.y * height];
+            let dimensions = [viewport.width * width, viewport.height * height];
+            builder.set_viewport(
                 0,
-                self.uniform_descriptor_set.clone(),
+                [Viewport {
+                    origin,
+                    dimensions,
+                    depth_range: 0.0..1.0,
+                }],
+            );
+            builder.set_scissor(
+                0,
+                [Scissor {
+                    origin: origin.map(|o| o.round() as u32),
+                    dimensions: dimensions.map(|d| d.round() as u32),
+                }],
             );
 
-            // record draw commands
-            for data in self.buffer_data.drain(..) {
-                TIME!("commandbuffer record buffer_data");
-                future = Box::new(future.join(data.future));
-
-                builder
-                    .bind_vertex_buffers(0, (data.vertex_buffer, data.instance_buffer))
-                    .draw(QUAD_INDICES.len() as u32, data.quads_count as u32, 0, 0)
-                    .unwrap();
+            // clear the viewport with a quad covering the whole clip space,
+            // the depth is cleared even when the color is kept so that every
+            // camera draws over the previous ones
+            let clip_space = Transform2D::new(Vector2::new(0.0, 0.0), Vector2::new(2.0, 2.0));
+            let (clear_pipeline, color) = match view.clear_color {
+                Some(color) => (self.clear_pipeline.clone(), color),
+                None => (self.clear_depth_pipeline.clone(), [0.0; 4]),
+            };
+            let clear = [QuadVertexInstance::new(&clip_space, &color, &UvRect::FULL)];
+            builder.bind_pipeline_graphics(clear_pipeline.clone());
+            future = self.record_batch(
+                &mut builder,
+                future,
+                &clear_pipeline,
+                Matrix4::identity(),
+                TextureHandle::WHITE,
+                &clear,
+            );
+            self.stats.draw_calls += 1;
+
+            let in_layers = instances
+                .iter()
+                .zip(&quads)
+                .filter(|(_, quad)| quad.layer & view.layer_mask != 0);
+            let mut visible: Vec<_> = if self.culling {
+                let mut culled = 0;
+                let visible = in_layers
+                    .filter(|(_, quad)| {
+                        let visible = in_view(&quad.corners, &view.view_projection);
+                        culled += usize::from(!visible);
+                        visible
+                    })
+                    .collect();
+                self.stats.culled += culled;
+                visible
+            } else {
+                in_layers.collect()
+            };
+            self.stats.drawn += visible.len();
+
+            for batch in plan_batches(&mut visible, self.max_quads, &mut self.stats) {
+                TIME!("commandbuffer record batch");
+                let quads = &visible[batch];
+                let texture = quads[0].1.texture;
+                let pipeline = self.quad_pipeline(quads[0].1);
+                let instances: Vec<_> = quads.iter().map(|(instance, _)| **instance).collect();
+                builder.bind_pipeline_graphics(pipeline.clone());
+                future = self.record_batch(
+                    &mut builder,
+                    future,
+                    &pipeline,
+                    view.view_projection,
+                    texture,
+                    &instances,
+                );
             }
         }
 
+        // keep the allocations for the next frame
+        self.instances = instances;
+        self.instances.clear();
+        self.quads = quads;
+        self.quads.clear();
+
         let command_buffer = builder.build().unwrap();
-        let future = Box::new(future);
 
         Some((command_buffer, future))
     }
 
-    pub fn copy_uniforms(&mut self, mvp: Matrix4<f32>) -> Result<PrimaryAutoCommandBuffer> {
-        let subbuffer = self
-            .uniform_buffer
-            .next(vs::ty::UniformBufferObject { mvp: mvp.into() })?;
-
-        let mut cbb = AutoCommandBufferBuilder::primary(
-            self.gfx_queue.device().clone(),
-            self.gfx_queue.family(),
-            CommandBufferUsage::OneTimeSubmit,
-        )?;
-        cbb.copy_buffer(CopyBufferInfo::buffers(
-            subbuffer,
-            self.uniform_buffer_dev.clone(),
-        ))?;
-        let cb = cbb.build()?;
-
-        Ok(cb)
-    }
-
-    fn flush_batch(&mut self) {
-        TIME!("pipeline.flush_batch");
-        if self.quads_count == 0 {
-            return;
+    fn quad_pipeline(&self, quad: &QuadInfo) -> Arc<GraphicsPipeline> {
+        if quad.opaque {
+            self.pipeline.clone()
+        } else {
+            self.transparent_pipelines[quad.blend as usize].clone()
         }
+    }
 
-        // create vertex and index buffer from quad renderer
-        let (vertex_buffer, vb_future) = ImmutableBuffer::from_iter(
-            QUAD_INDICES.into_iter().map(|idx| {
-                QuadVertex::new(
-                    &QUAD_VERTICES[idx as usize].into(),
-                    &QUAD_TEX_COORDS[idx as usize].into(),
-                )
-            }),
-            BufferUsage::vertex_buffer_transfer_dst(),
-            self.gfx_queue.clone(),
-        )
-        .unwrap();
-        let (instance_buffer, ib_future) = ImmutableBuffer::from_iter(
-            self.instances.clone(),
+    // record a single draw call with the bound pipeline, returns the future of
+    // the uploaded data
+    fn record_batch(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<SecondaryAutoCommandBuffer>,
+        future: Box<dyn GpuFuture>,
+        pipeline: &Arc<GraphicsPipeline>,
+        mvp: Matrix4<f32>,
+        texture: TextureHandle,
+        instances: &[QuadVertexInstance],
+    ) -> Box<dyn GpuFuture> {
+        let (instance_buffer, instance_future) = ImmutableBuffer::from_iter(
+            instances.iter().copied(),
             BufferUsage::vertex_buffer_transfer_dst(),
             self.gfx_queue.clone(),
         )
         .unwrap();
+        let descriptor_set = self
+            .descriptor_set(pipeline, mvp, texture)
+            .expect("create descriptor set for mvp uniform buffer");
 
-        // join both futures
-        let future = Box::new(vb_future.join(ib_future));
-
-        // save quads_count before reset
-        let quads_count = self.quads_count;
-
-        // reset batcher values
-        self.reset_batch();
+        builder
+            .bind_descriptor_sets(
+                vulkano::pipeline::PipelineBindPoint::Graphics,
+                pipeline.layout().clone(),
+                0,
+                descriptor_set,
+            )
+            .bind_vertex_buffers(0, (self.vertex_buffer.clone(), instance_buffer))
+            .draw(QUAD_INDICES.len() as u32, instances.len() as u32, 0, 0)
+            .unwrap();
 
-        // push buffer data onto vec
-        self.buffer_data.push(QuadBufferData {
-            quads_count,
-            vertex_buffer,
-            instance_buffer,
-            future,
-        })
+        future.join(instance_future).boxed()
     }
 
-    fn reset_batch(&mut self) {
-        self.quads_count = 0;
-        self.vertices.clear();
-        self.instances.clear();
+    fn descriptor_set(
+        &self,
+        pipeline: &Arc<GraphicsPipeline>,
+        mvp: Matrix4<f32>,
+        texture: TextureHandle,
+    ) -> Result<Arc<PersistentDescriptorSet>> {
+        let subbuffer = self
+            .uniform_buffer
+            .next(vs::ty::UniformBufferObject { mvp: mvp.into() })?;
+        let layout = pipeline.layout().set_layouts().get(0).unwrap();
+        let descriptor_set = PersistentDescriptorSet::new(
+            layout.clone(),
+            [
+                WriteDescriptorSet::buffer(0, subbuffer),
+                WriteDescriptorSet::image_view_sampler(
+                    1,
+                    self.textures[texture.0].view.clone(),
+                    self.sampler.clone(),
+                ),
+            ],
+        )?;
+
+        Ok(descriptor_set)
     }
 
-    #[allow(dead_code)]
     #[allow(clippy::type_complexity)]
     fn upload_texture(
         &self,
@@ -407,25 +569,190 @@ impl QuadPipeline {
     }
 }
 
-#[allow(dead_code)]
+// Quads are culled when their corners, at the z of the quad, are all on the
+// same side of the view. Quads partly behind a perspective camera are kept.
+fn in_view(corners: &[Vector3<f32>; 4], view_projection: &Matrix4<f32>) -> bool {
+    let clip = corners.map(|corner| view_projection * corner.extend(1.0));
+    if clip.iter().all(|c| c.w <= 0.0) {
+        return false;
+    }
+    if clip.iter().any(|c| c.w <= 0.0) {
+        return true;
+    }
+    let outside = |f: fn(&Vector4<f32>) -> bool| clip.iter().all(f);
+    !(outside(|c| c.x < -c.w)
+        || outside(|c| c.x > c.w)
+        || outside(|c| c.y < -c.w)
+        || outside(|c| c.y > c.w))
+}
+
+// The depth buffer stores the draw order of the quads rather than their z:
+// quads with a greater z are in front, and quads with the same z are drawn in
+// the order they were submitted, later ones in front. Each quad gets its own
+// depth between 0.0 (closest) and CLEAR_DEPTH, so that opaque quads can be
+// drawn in any order.
+fn draw_depths(quads: &[QuadInfo]) -> Vec<f32> {
+    let mut order: Vec<_> = (0..quads.len()).collect();
+    // stable: quads with the same z keep the submission order
+    order.sort_by(|a, b| quads[*a].z.total_cmp(&quads[*b].z));
+    let mut depths = vec![CLEAR_DEPTH; quads.len()];
+    let steps = (quads.len() + 1) as f32;
+    for (rank, index) in order.into_iter().enumerate() {
+        depths[index] = CLEAR_DEPTH * (1.0 - (rank + 1) as f32 / steps);
+    }
+    depths
+}
+
+// Order the quads of a view for drawing and split them into draw calls
+// sharing a texture and a pipeline. Opaque quads come first, grouped by
+// texture since the depth test hides what is behind them whatever their
+// order. Translucent quads follow, from the farthest to the closest so that
+// they blend with what is behind them, quads at the same depth keep the order
+// they were submitted in.
+fn plan_batches<I>(
+    quads: &mut [(I, &QuadInfo)],
+    max_quads: usize,
+    stats: &mut RenderStats,
+) -> Vec<Range<usize>> {
+    quads.sort_by(|(_, a), (_, b)| match (a.opaque, b.opaque) {
+        (true, true) => a.texture.0.cmp(&b.texture.0),
+        (false, false) => a.z.total_cmp(&b.z),
+        (a, b) => b.cmp(&a),
+    });
+
+    // opaque quads share a pipeline whatever their blend mode
+    let batch_key = |quad: &QuadInfo| (quad.texture, (!quad.opaque).then_some(quad.blend));
+    let mut batches = Vec::new();
+    let mut start = 0;
+    while start < quads.len() {
+        let key = batch_key(quads[start].1);
+        let end = quads[start..]
+            .iter()
+            .position(|(_, quad)| batch_key(quad) != key)
+            .map_or(quads.len(), |n| start + n);
+        for batch_start in (start..end).step_by(max_quads.max(1)) {
+            batches.push(batch_start..(batch_start + max_quads).min(end));
+        }
+        start = end;
+    }
+    stats.draw_calls += batches.len();
+    batches
+}
+
+fn build_pipeline(
+    gfx_queue: &Arc<Queue>,
+    subpass: Subpass,
+    depth_stencil_state: DepthStencilState,
+    color_blend_state: ColorBlendState,
+) -> Arc<GraphicsPipeline> {
+    // compile shaders
+    let vs = vs::load(gfx_queue.device().clone()).expect("failed to create vertex shader module");
+    let fs = fs::load(gfx_queue.device().clone()).expect("failed to create fragment shader module");
+    // create graphics pipeline
+    GraphicsPipeline::start()
+        .vertex_input_state(
+            BuffersDefinition::new()
+                .vertex::<QuadVertex>()
+                .instance::<QuadVertexInstance>(),
+        )
+        .vertex_shader(vs.entry_point("main").unwrap(), ())
+        .input_assembly_state(InputAssemblyState::new())
+        .fragment_shader(fs.entry_point("main").unwrap(), ())
+        .viewport_state(ViewportState::viewport_dynamic_scissor_dynamic(1))
+        .depth_stencil_state(depth_stencil_state)
+        .color_blend_state(color_blend_state)
+        .render_pass(subpass)
+        .build(gfx_queue.device().clone())
+        .unwrap()
+}
+
+// blend factors of the color and alpha channels of each blend mode, the
+// colors are not premultiplied unless told so
+fn attachment_blend(blend: BlendMode) -> AttachmentBlend {
+    let (color_source, color_destination, alpha_source, alpha_destination) = match blend {
+        BlendMode::Alpha => (
+            BlendFactor::SrcAlpha,
+            BlendFactor::OneMinusSrcAlpha,
+            BlendFactor::One,
+            BlendFactor::OneMinusSrcAlpha,
+        ),
+        BlendMode::Premultiplied => (
+            BlendFactor::One,
+            BlendFactor::OneMinusSrcAlpha,
+            BlendFactor::One,
+            BlendFactor::OneMinusSrcAlpha,
+        ),
+        BlendMode::Additive => (
+            BlendFactor::SrcAlpha,
+            BlendFactor::One,
+            BlendFactor::Zero,
+            BlendFactor::One,
+        ),
+        BlendMode::Multiply => (
+            BlendFactor::DstColor,
+            BlendFactor::Zero,
+            BlendFactor::Zero,
+            BlendFactor::One,
+        ),
+        BlendMode::Screen => (
+            BlendFactor::One,
+            BlendFactor::OneMinusSrcColor,
+            BlendFactor::Zero,
+            BlendFactor::One,
+        ),
+    };
+    AttachmentBlend {
+        color_op: BlendOp::Add,
+        color_source,
+        color_destination,
+        alpha_op: BlendOp::Add,
+        alpha_source,
+        alpha_destination,
+    }
+}
+
+fn depth_state(write: bool, compare_op: CompareOp) -> DepthStencilState {
+    DepthStencilState {
+        depth: Some(DepthState {
+            enable_dynamic: false,
+            write_enable: StateMode::Fixed(write),
+            compare_op: StateMode::Fixed(compare_op),
+        }),
+        ..DepthStencilState::disabled()
+    }
+}
+
+// decode a PNG image to 8 bits RGBA
 fn load_image_png(img_bytes: Vec<u8>) -> Result<(Vec<u8>, ImageDimensions)> {
     let cursor = Cursor::new(img_bytes);
-    let decoder = png::Decoder::new(cursor);
+    let mut decoder = png::Decoder::new(cursor);
+    // expand palettes and small bit depths, strip 16 bits channels
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
     let mut reader = decoder.read_info()?;
 
-    // TODO: use info to extract ColorType and BitDepth
-    //       and determine the texture Format
-    let info = reader.info();
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    let pixels = &buffer[..info.buffer_size()];
+    let image_data = match info.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => pixels
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => return Err("unexpected indexed PNG after expansion".into()),
+    };
+
     let dimensions = ImageDimensions::Dim2d {
         width: info.width,
         height: info.height,
         array_layers: 1,
     };
 
-    let mut image_data = Vec::new();
-    image_data.resize((dimensions.width() * dimensions.height() * 4) as usize, 0);
-    reader.next_frame(&mut image_data)?;
-
     Ok((image_data, dimensions))
 }
 
@@ -454,15 +781,27 @@ layout(location = 1) in vec2 tex_coords;
 layout(location = 2) in vec2 offset;
 layout(location = 3) in vec2 scale;
 layout(location = 4) in vec4 color;
+layout(location = 5) in vec2 uv_offset;
+layout(location = 6) in vec2 uv_scale;
+layout(location = 7) in float rotation;
+layout(location = 8) in vec2 pivot;
+layout(location = 9) in float z;
+layout(location = 10) in float depth;
 
 // outputs
 layout(location = 0) out vec2 f_tex_coords;
 layout(location = 1) out vec4 f_frag_color;
 
 void main() {
-    f_tex_coords = tex_coords;
+    f_tex_coords = uv_offset + tex_coords * uv_scale;
     f_frag_color = color;
-    gl_Position = ubo.mvp * vec4(position * scale + offset, 0.0, 1.0);
+    vec2 local = (position - pivot) * scale;
+    float c = cos(rotation);
+    float s = sin(rotation);
+    vec2 rotated = vec2(local.x * c - local.y * s, local.x * s + local.y * c);
+    gl_Position = ubo.mvp * vec4(rotated + offset, z, 1.0);
+    // the draw order replaces the depth of the projection
+    gl_Position.z = depth * gl_Position.w;
 }"
     }
 }
@@ -475,7 +814,7 @@ pub mod fs {
 #version 450
 
 // uniforms
-layout(binding = 1) uniform sampler2DArray tex;
+layout(binding = 1) uniform sampler2D tex;
 
 // inputs
 layout(location = 0) in vec2 tex_coords;
@@ -485,10 +824,108 @@ layout(location = 1) in vec4 frag_color;
 layout(location = 0) out vec4 color;
 
 void main() {
-    // color = frag_color;
-    // color = vec4(tex_coords, 0.0, 1.0);
-    // color = texture(white_tex[0], tex_coords) * frag_color;
-    color = texture(tex, vec3(tex_coords, 0)) * frag_color;
+    color = texture(tex, tex_coords) * frag_color;
 }"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{perspective, Angle, Deg, Point3};
+
+    use super::*;
+
+    fn quad(texture: usize, z: f32, opaque: bool) -> QuadInfo {
+        QuadInfo {
+            layer: 1,
+            texture: TextureHandle(texture),
+            corners: Transform2D::new(Vector2::new(0.0, 0.0), Vector2::new(1.0, 1.0))
+                .corners()
+                .map(|corner| corner.extend(z)),
+            z,
+            blend: BlendMode::Alpha,
+            opaque,
+        }
+    }
+
+    fn draw_calls(quads: &[QuadInfo], max_quads: usize) -> (Vec<usize>, RenderStats) {
+        let mut stats = RenderStats::default();
+        let mut planned: Vec<_> = quads.iter().enumerate().collect();
+        plan_batches(&mut planned, max_quads, &mut stats);
+        (planned.iter().map(|(i, _)| *i).collect(), stats)
+    }
+
+    #[test]
+    fn interleaved_textures() {
+        // opaque quads are grouped by texture
+        let opaque: Vec<_> = (0..6).map(|i| quad(1 + i % 2, 0.0, true)).collect();
+        let (order, stats) = draw_calls(&opaque, 100);
+        assert_eq!(stats.draw_calls, 2);
+        assert_eq!(order, [0, 2, 4, 1, 3, 5]);
+
+        // and split when a batch is full
+        let (_, stats) = draw_calls(&opaque, 2);
+        assert_eq!(stats.draw_calls, 4);
+
+        // translucent quads keep their order at the same depth, and are drawn
+        // after the opaque ones from the farthest to the closest
+        let mut quads: Vec<_> = (0..4).map(|i| quad(1 + i % 2, 0.0, false)).collect();
+        quads.push(quad(1, -1.0, false));
+        quads.push(quad(2, 0.0, true));
+        let (order, stats) = draw_calls(&quads, 100);
+        assert_eq!(order, [5, 4, 0, 1, 2, 3]);
+        assert_eq!(stats.draw_calls, 5);
+    }
+
+    #[test]
+    fn cull_at_the_depth_of_the_quad() {
+        // perspective camera 2 units away from the z = 0 plane
+        let fov = Deg(60.0);
+        let aspect = 4.0 / 3.0;
+        let view_projection = perspective(fov, aspect, 0.1, 100.0)
+            * Matrix4::look_at_rh(
+                Point3::new(0.0, 0.0, 2.0),
+                Point3::new(0.0, 0.0, 0.0),
+                Vector3::unit_y(),
+            );
+        // half the width seen at the given z
+        let half_width = |z: f32| (2.0 - z) * (fov / 2.0).tan() * aspect;
+        let quad_at = |x: f32, z: f32| {
+            Transform2D::new(Vector2::new(x, 0.0), Vector2::new(0.2, 0.2))
+                .corners()
+                .map(|corner| corner.extend(z))
+        };
+
+        // a quad at z = 1 crossing the right border is drawn, one just past
+        // it is culled although it is inside the area seen at z = 0
+        assert!(in_view(&quad_at(half_width(1.0), 1.0), &view_projection));
+        assert!(!in_view(
+            &quad_at(half_width(1.0) + 0.2, 1.0),
+            &view_projection
+        ));
+        assert!(half_width(1.0) + 0.2 < half_width(0.0));
+
+        // further away, the view is wider than at z = 0
+        assert!(in_view(
+            &quad_at(half_width(0.0) + 0.2, -1.0),
+            &view_projection
+        ));
+
+        // behind the camera
+        assert!(!in_view(&quad_at(0.0, 3.0), &view_projection));
+    }
+
+    #[test]
+    fn same_depth_keeps_submission_order() {
+        // a sprite submitted after its background stays in front of it even
+        // though its texture is drawn first
+        let quads = [quad(2, 0.0, true), quad(1, 0.0, true), quad(3, -1.0, true)];
+        let (order, _) = draw_calls(&quads, 100);
+        assert_eq!(order, [1, 0, 2]);
+
+        let depths = draw_depths(&quads);
+        assert!(depths[1] < depths[0]);
+        assert!(depths[0] < depths[2]);
+        assert!(depths.iter().all(|d| *d > 0.0 && *d < CLEAR_DEPTH));
+    }
+}
@@ -1,28 +1,34 @@
 use std::{error::Error, result, sync::Arc};
 
-use cgmath::{Matrix4, Vector2, Vector4};
+use cgmath::Vector4;
 use vulkano::{
     command_buffer::{
         AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer, SubpassContents,
     },
     device::Queue,
     format::Format,
-    image::{ImageAccess, ImageViewAbstract},
+    image::{view::ImageView, AttachmentImage, ImageAccess, ImageViewAbstract},
     render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass, Subpass},
     sync::{self, GpuFuture},
 };
 
-use super::pipeline::QuadPipeline;
+use super::pipeline::{QuadPipeline, TextureHandle};
+use crate::render::{BlendMode, RenderStats, RenderView, Transform2D, UvRect};
 use crate::TIME;
 
 type Result<T> = result::Result<T, Box<dyn Error>>;
 
+// 32 bits so that every quad of a frame gets its own depth
+const DEPTH_FORMAT: Format = Format::D32_SFLOAT;
+
 // QuadRenderPass is responsible for creating a render pass and a graphics
 // pipeline.
 pub struct QuadRenderPass {
     gfx_queue: Arc<Queue>,
     render_pass: Arc<RenderPass>,
     pipeline: QuadPipeline,
+    // recreated when the size of the target changes
+    depth_buffer: Option<Arc<ImageView<AttachmentImage>>>,
 }
 
 impl QuadRenderPass {
@@ -37,11 +43,17 @@ impl QuadRenderPass {
                     store: Store,
                     format: output_format,
                     samples: 1,
+                },
+                depth: {
+                    load: Clear,
+                    store: DontCare,
+                    format: DEPTH_FORMAT,
+                    samples: 1,
                 }
             },
             pass: {
                 color: [color],
-                depth_stencil: {}
+                depth_stencil: {depth}
             }
         )
         .unwrap();
@@ -56,6 +68,7 @@ impl QuadRenderPass {
             gfx_queue,
             render_pass,
             pipeline,
+            depth_buffer: None,
         }
     }
 
@@ -64,45 +77,59 @@ impl QuadRenderPass {
         before_future: Box<dyn GpuFuture>,
         image_view: Arc<dyn ImageViewAbstract>,
         clear_value: [f32; 4],
-        mvp: Matrix4<f32>,
+        views: &[RenderView],
     ) -> Box<dyn GpuFuture> {
         TIME!("renderpass.render");
 
-        // create command buffer for copying uniform data
-        let uniforms_cb = self
-            .pipeline
-            .copy_uniforms(mvp)
-            .expect("create uniform command buffer");
-
         // record render commands into command buffer
-        let (renderpass_cb, renderpass_future) =
-            self.record_command_buffer(image_view, clear_value).unwrap();
+        let (renderpass_cb, renderpass_future) = self
+            .record_command_buffer(image_view, clear_value, views)
+            .unwrap();
 
         // Execute command buffers
         let after_future = before_future
             .join(renderpass_future)
-            .then_execute(self.gfx_queue.clone(), uniforms_cb)
-            .unwrap()
             .then_execute(self.gfx_queue.clone(), renderpass_cb)
             .unwrap();
 
         after_future.boxed()
     }
 
-    pub fn draw_quad(&mut self, position: Vector2<f32>, size: Vector2<f32>, color: Vector4<f32>) {
-        self.pipeline.add_quad(position, size, color)
+    pub fn draw_quad(
+        &mut self,
+        transform: Transform2D,
+        color: Vector4<f32>,
+        uv: UvRect,
+        texture: TextureHandle,
+        blend: BlendMode,
+    ) {
+        self.pipeline.add_quad(transform, color, uv, texture, blend)
+    }
+
+    pub fn load_texture(&mut self, png_bytes: Vec<u8>) -> Result<TextureHandle> {
+        self.pipeline.load_texture(png_bytes)
+    }
+
+    pub fn set_culling(&mut self, b: bool) {
+        self.pipeline.set_culling(b)
+    }
+
+    pub fn stats(&self) -> RenderStats {
+        self.pipeline.stats()
     }
 
     fn record_command_buffer(
         &mut self,
         image_view: Arc<dyn ImageViewAbstract>,
         clear_value: [f32; 4],
+        views: &[RenderView],
     ) -> Result<(PrimaryAutoCommandBuffer, Box<dyn GpuFuture>)> {
         let dimensions = image_view.clone().image().dimensions();
+        let depth_buffer = self.depth_buffer(dimensions.width_height())?;
         let framebuffer = Framebuffer::new(
             self.render_pass.clone(),
             FramebufferCreateInfo {
-                attachments: vec![image_view],
+                attachments: vec![image_view, depth_buffer as Arc<dyn ImageViewAbstract>],
                 ..Default::default()
             },
         )
@@ -119,14 +146,16 @@ impl QuadRenderPass {
             .begin_render_pass(
                 framebuffer,
                 SubpassContents::SecondaryCommandBuffers,
-                vec![clear_value.into()],
+                vec![clear_value.into(), 1f32.into()],
             )
             .unwrap();
 
         // Create secondary command buffer from texture pipeline & send draw
         // commands
         let mut future = sync::now(self.gfx_queue.device().clone()).boxed();
-        if let Some((draw_cb, buffers_future)) = self.pipeline.draw(dimensions.width_height()) {
+        if let Some((draw_cb, buffers_future)) =
+            self.pipeline.draw(dimensions.width_height(), views)
+        {
             future = Box::new(future.join(buffers_future));
             // Execute above commands (subpass)
             command_buffer_builder.execute_commands(draw_cb).unwrap();
@@ -138,4 +167,17 @@ impl QuadRenderPass {
 
         Ok((command_buffer, future))
     }
+
+    fn depth_buffer(&mut self, dimensions: [u32; 2]) -> Result<Arc<ImageView<AttachmentImage>>> {
+        if let Some(depth_buffer) = &self.depth_buffer {
+            if depth_buffer.image().dimensions().width_height() == dimensions {
+                return Ok(depth_buffer.clone());
+            }
+        }
+        let image =
+            AttachmentImage::transient(self.gfx_queue.device().clone(), dimensions, DEPTH_FORMAT)?;
+        let depth_buffer = ImageView::new_default(image)?;
+        self.depth_buffer = Some(depth_buffer.clone());
+        Ok(depth_buffer)
+    }
 }
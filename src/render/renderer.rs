@@ -3,15 +3,22 @@ use std::ops::Mul;
 use std::result;
 use std::sync::Arc;
 
-use cgmath::{Matrix4, SquareMatrix, Vector2, Vector4};
+use cgmath::{Matrix4, Vector2, Vector4};
 use log::error;
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, BlitImageInfo, ClearColorImageInfo, CommandBufferUsage, ImageBlit,
+};
+use vulkano::image::{view::ImageView, AttachmentImage, ImageAccess, ImageUsage};
+use vulkano::sampler::Filter;
 use vulkano::swapchain::AcquireError;
 use vulkano::sync::{FlushError, GpuFuture};
 use vulkano::{swapchain, sync};
 use winit::event::{Event, WindowEvent};
 use winit::window::Window;
 
-use super::quad::QuadRenderPass;
+use super::camera::ViewportRect;
+use super::quad::{QuadRenderPass, TextureHandle};
+use super::{BlendMode, Transform2D, UvRect};
 use crate::render::{Device, DeviceDefinition};
 use crate::TIME;
 
@@ -22,13 +29,48 @@ const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
 // Vulkan clip space has inverted Y and half Z.
 // https://matthewwellings.com/blog/the-new-vulkan-coordinate-system/
 #[rustfmt::skip]
-const VULKAN_COORD_MAGIC_PROJ: Matrix4<f32> = Matrix4::new(
+pub const VULKAN_COORD_MAGIC_PROJ: Matrix4<f32> = Matrix4::new(
     1.0, 0.0, 0.0, 0.0,
     0.0, -1.0, 0.0, 0.0,
     0.0, 0.0, 0.5, 0.0,
     0.0, 0.0, 0.5, 1.0,
 );
 
+// RenderView is a camera drawn during the frame.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderView {
+    pub view_projection: Matrix4<f32>,
+    // area of the swapchain image the quads are drawn to
+    pub viewport: ViewportRect,
+    // color the viewport is cleared with before drawing
+    pub clear_color: Option<[f32; 4]>,
+    // layers of quads drawn, one bit per layer
+    pub layer_mask: u32,
+}
+
+// RenderStats counts what was drawn during the last frame. Quads are counted
+// once per view drawing them.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct RenderStats {
+    // quads submitted by the application
+    pub quads: usize,
+    // quads sent to the GPU
+    pub drawn: usize,
+    // quads outside of the visible area of a view
+    pub culled: usize,
+    pub draw_calls: usize,
+}
+
+// LowResolutionTarget is an offscreen image the views are drawn to, then
+// upscaled to an area of the window without filtering.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LowResolutionTarget {
+    // size of the image, in pixels
+    pub size: [u32; 2],
+    // area of the window the image is upscaled to
+    pub viewport: ViewportRect,
+}
+
 // inspiration: https://github.com/vulkano-rs/vulkano/tree/master/examples/src/bin/interactive_fractal
 pub struct Renderer2D {
     device: Device,
@@ -40,6 +82,10 @@ pub struct Renderer2D {
 
     render_pass: QuadRenderPass,
 
+    // pixel perfect rendering
+    low_resolution: Option<LowResolutionTarget>,
+    low_resolution_image: Option<Arc<ImageView<AttachmentImage>>>,
+
     frame_future: Option<Box<dyn GpuFuture>>,
 
     fences: Vec<Option<Box<dyn GpuFuture>>>,
@@ -60,6 +106,8 @@ impl Renderer2D {
             background_color: BLACK,
             should_recreate_swapchain: false,
             render_pass,
+            low_resolution: None,
+            low_resolution_image: None,
             frame_future: None,
             fences: std::iter::repeat_with(|| None)
                 .take(frames_in_flight)
@@ -71,8 +119,10 @@ impl Renderer2D {
     }
 
     pub fn on_event(&mut self, event: &Event<()>) {
+        // the swapchain extent is expressed in physical pixels and must follow
+        // both the window size and the scale factor
         if let Event::WindowEvent {
-            event: WindowEvent::Resized(_),
+            event: WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. },
             ..
         } = event
         {
@@ -84,6 +134,25 @@ impl Renderer2D {
         self.background_color = *c;
     }
 
+    // draw the next frames to a low resolution image, None draws directly to
+    // the window
+    pub fn set_low_resolution(&mut self, target: Option<LowResolutionTarget>) {
+        self.low_resolution = target;
+        if target.is_none() {
+            self.low_resolution_image = None;
+        }
+    }
+
+    // quads outside of the view are not sent to the GPU, disable to check
+    // what the culling removes
+    pub fn set_culling(&mut self, b: bool) {
+        self.render_pass.set_culling(b)
+    }
+
+    pub fn stats(&self) -> RenderStats {
+        self.render_pass.stats()
+    }
+
     pub fn window_resized(&mut self) {
         self.should_recreate_swapchain = true;
     }
@@ -139,14 +208,13 @@ impl Renderer2D {
         Ok(())
     }
 
-    pub fn end_frame(&mut self, vp: Matrix4<f32>) {
+    // the quads of the frame are drawn once per view, in order
+    pub fn end_frame(&mut self, views: &[RenderView]) {
         TIME!("renderer.end");
         let frame_future = self
             .frame_future
             .take()
             .expect("frame future should not be none in renderer.end()");
-        let model = Matrix4::identity();
-        let mvp = model.mul(vp);
         // Pre-multiply mvp matrix with this magix matrix
         // to adapt to Vulkan coordinate system.
         //
@@ -160,15 +228,24 @@ impl Renderer2D {
         //   gl_Position.z = (gl_Position.z + gl_Position.w) / 2.0;
         //
         // ref: https://matthewwellings.com/blog/the-new-vulkan-coordinate-system/
-        let mvp = VULKAN_COORD_MAGIC_PROJ.mul(mvp);
+        let views: Vec<_> = views
+            .iter()
+            .map(|view| RenderView {
+                view_projection: VULKAN_COORD_MAGIC_PROJ.mul(view.view_projection),
+                ..*view
+            })
+            .collect();
 
         // submit graphics quads render pass (submit command buffer)
-        let render_future = self.render_pass.render(
-            frame_future,
-            self.device.image_view(),
-            self.background_color,
-            mvp,
-        );
+        let render_future = match self.low_resolution {
+            Some(target) => self.render_low_resolution(frame_future, target, &views),
+            None => self.render_pass.render(
+                frame_future,
+                self.device.image_view(),
+                self.background_color,
+                &views,
+            ),
+        };
 
         // present swapchain image
         // TODO: this statement generates a stack overflow error when trying to render
@@ -197,7 +274,168 @@ impl Renderer2D {
     }
 
     pub fn draw_quad(&mut self, position: Vector2<f32>, size: Vector2<f32>, color: Vector4<f32>) {
-        self.render_pass.draw_quad(position, size, color)
+        self.draw_quad_on_layer(position, size, color, 0)
+    }
+
+    // only the cameras whose layer mask contains the layer draw the quad,
+    // layers go from 0 to 31
+    pub fn draw_quad_on_layer(
+        &mut self,
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        color: Vector4<f32>,
+        layer: u32,
+    ) {
+        self.draw_quad_ex(
+            Transform2D::new(position, size).with_layer(layer),
+            color,
+            TextureHandle::WHITE,
+            UvRect::FULL,
+        )
+    }
+
+    // the texture is multiplied by the tint color
+    pub fn draw_textured_quad(
+        &mut self,
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        texture: TextureHandle,
+        tint: Vector4<f32>,
+    ) {
+        self.draw_sprite(position, size, texture, UvRect::FULL, tint)
+    }
+
+    // draw an area of a texture, for example a frame of a sprite sheet
+    pub fn draw_sprite(
+        &mut self,
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        texture: TextureHandle,
+        uv: UvRect,
+        tint: Vector4<f32>,
+    ) {
+        self.draw_quad_ex(Transform2D::new(position, size), tint, texture, uv)
+    }
+
+    // draw a rotated quad on the layer of its transform, the color tints the
+    // texture
+    pub fn draw_quad_ex(
+        &mut self,
+        transform: Transform2D,
+        color: Vector4<f32>,
+        texture: TextureHandle,
+        uv: UvRect,
+    ) {
+        self.draw_quad_blended(transform, color, texture, uv, BlendMode::Alpha)
+    }
+
+    // draw a quad combined with what is behind it by the blend mode
+    pub fn draw_quad_blended(
+        &mut self,
+        transform: Transform2D,
+        color: Vector4<f32>,
+        texture: TextureHandle,
+        uv: UvRect,
+        blend: BlendMode,
+    ) {
+        self.render_pass
+            .draw_quad(transform, color, uv, texture, blend)
+    }
+
+    // load a PNG image, the texture stays loaded until the renderer is dropped
+    pub fn load_texture(&mut self, png_bytes: &[u8]) -> Result<TextureHandle> {
+        self.render_pass.load_texture(png_bytes.to_vec())
+    }
+
+    // draw the views to the low resolution image and upscale it to the
+    // swapchain image, each pixel of the image becoming a block of pixels
+    fn render_low_resolution(
+        &mut self,
+        before_future: Box<dyn GpuFuture>,
+        target: LowResolutionTarget,
+        views: &[RenderView],
+    ) -> Box<dyn GpuFuture> {
+        TIME!("renderer.low_resolution");
+        let image = self.low_resolution_image(target.size);
+
+        // the views are expressed in the window, the image only covers the
+        // target viewport
+        let views: Vec<_> = views
+            .iter()
+            .map(|view| RenderView {
+                viewport: view.viewport.relative_to(&target.viewport),
+                ..*view
+            })
+            .collect();
+        let future =
+            self.render_pass
+                .render(before_future, image.clone(), self.background_color, &views);
+
+        let output = self.device.image_view().image();
+        let [width, height] = output.dimensions().width_height().map(|d| d as f32);
+        let area = target.viewport;
+        let origin = [area.x * width, area.y * height].map(|o| o.round() as u32);
+        let size = [area.width * width, area.height * height].map(|d| d.round() as u32);
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.device.device.clone(),
+            self.device.graphics_queue().family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        // clear the bars around the upscaled image
+        builder
+            .clear_color_image(ClearColorImageInfo {
+                clear_value: self.background_color.into(),
+                ..ClearColorImageInfo::image(output.clone())
+            })
+            .unwrap();
+        builder
+            .blit_image(BlitImageInfo {
+                regions: [ImageBlit {
+                    src_subresource: image.image().subresource_layers(),
+                    src_offsets: [[0, 0, 0], [target.size[0], target.size[1], 1]],
+                    dst_subresource: output.subresource_layers(),
+                    dst_offsets: [
+                        [origin[0], origin[1], 0],
+                        [origin[0] + size[0], origin[1] + size[1], 1],
+                    ],
+                    ..Default::default()
+                }]
+                .into(),
+                filter: Filter::Nearest,
+                ..BlitImageInfo::images(image.image().clone(), output)
+            })
+            .unwrap();
+        let command_buffer = builder.build().unwrap();
+
+        future
+            .then_execute(self.device.graphics_queue(), command_buffer)
+            .unwrap()
+            .boxed()
+    }
+
+    // the image is recreated when the size of the target changes
+    fn low_resolution_image(&mut self, size: [u32; 2]) -> Arc<ImageView<AttachmentImage>> {
+        if let Some(image) = &self.low_resolution_image {
+            if image.image().dimensions().width_height() == size {
+                return image.clone();
+            }
+        }
+        let image = AttachmentImage::with_usage(
+            self.device.device.clone(),
+            size,
+            self.device.swapchain.image_format(),
+            ImageUsage {
+                color_attachment: true,
+                transfer_src: true,
+                ..ImageUsage::none()
+            },
+        )
+        .expect("create low resolution image");
+        let image = ImageView::new_default(image).expect("create low resolution image view");
+        self.low_resolution_image = Some(image.clone());
+        image
     }
 
     fn recreate_swapchain_and_views(&mut self) {
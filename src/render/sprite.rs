@@ -0,0 +1,326 @@
+use std::{collections::HashMap, time::Duration};
+
+use super::TextureHandle;
+
+// frames shorter than this are stretched, so that an animation always moves
+// forward
+const MIN_FRAME_DURATION: Duration = Duration::from_millis(1);
+
+// UvRect is the area of a texture drawn on a quad, expressed as fractions of
+// the texture size with the origin at the top-left corner. A negative width
+// or height flips the image.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UvRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl UvRect {
+    pub const FULL: UvRect = UvRect {
+        x: 0.0,
+        y: 0.0,
+        width: 1.0,
+        height: 1.0,
+    };
+
+    // area of a texture of the given size, in pixels
+    pub fn from_pixels(texture_size: [u32; 2], x: u32, y: u32, width: u32, height: u32) -> Self {
+        let [texture_width, texture_height] = texture_size.map(|d| d.max(1) as f32);
+        UvRect {
+            x: x as f32 / texture_width,
+            y: y as f32 / texture_height,
+            width: width as f32 / texture_width,
+            height: height as f32 / texture_height,
+        }
+    }
+
+    // mirror the image horizontally and/or vertically
+    pub fn flipped(self, horizontal: bool, vertical: bool) -> Self {
+        let mut uv = self;
+        if horizontal {
+            uv.x += uv.width;
+            uv.width = -uv.width;
+        }
+        if vertical {
+            uv.y += uv.height;
+            uv.height = -uv.height;
+        }
+        uv
+    }
+}
+
+// SpriteSheet describes the frames packed in a texture, laid out on a grid or
+// at arbitrary positions. Frames are identified by their index, named frames
+// by their name too.
+#[derive(Debug, Clone)]
+pub struct SpriteSheet {
+    texture: TextureHandle,
+    // in pixels
+    texture_size: [u32; 2],
+    frames: Vec<UvRect>,
+    names: HashMap<String, usize>,
+}
+
+impl SpriteSheet {
+    pub fn new(texture: TextureHandle, texture_size: [u32; 2]) -> Self {
+        Self {
+            texture,
+            texture_size,
+            frames: Vec::new(),
+            names: HashMap::new(),
+        }
+    }
+
+    // frames of the given size in pixels, left to right then top to bottom,
+    // spacing is the gap between two frames
+    pub fn grid(
+        texture: TextureHandle,
+        texture_size: [u32; 2],
+        frame_size: [u32; 2],
+        spacing: u32,
+    ) -> Self {
+        let mut sheet = Self::new(texture, texture_size);
+        let [frame_width, frame_height] = frame_size.map(|d| d.max(1));
+        let columns = (texture_size[0] + spacing) / (frame_width + spacing);
+        let rows = (texture_size[1] + spacing) / (frame_height + spacing);
+        for row in 0..rows {
+            for column in 0..columns {
+                sheet.frames.push(UvRect::from_pixels(
+                    texture_size,
+                    column * (frame_width + spacing),
+                    row * (frame_height + spacing),
+                    frame_width,
+                    frame_height,
+                ));
+            }
+        }
+        sheet
+    }
+
+    // add a named frame, in pixels, and return its index
+    pub fn add_frame(&mut self, name: &str, x: u32, y: u32, width: u32, height: u32) -> usize {
+        self.frames
+            .push(UvRect::from_pixels(self.texture_size, x, y, width, height));
+        let index = self.frames.len() - 1;
+        self.names.insert(name.to_owned(), index);
+        index
+    }
+
+    // name an existing frame, for example a frame of the grid
+    pub fn set_frame_name(&mut self, name: &str, index: usize) {
+        if index < self.frames.len() {
+            self.names.insert(name.to_owned(), index);
+        }
+    }
+
+    pub fn texture(&self) -> TextureHandle {
+        self.texture
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn frame(&self, index: usize) -> Option<UvRect> {
+        self.frames.get(index).copied()
+    }
+
+    pub fn frame_index(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    pub fn named_frame(&self, name: &str) -> Option<UvRect> {
+        self.frame_index(name).and_then(|index| self.frame(index))
+    }
+}
+
+// AnimationMode decides what happens after the last frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AnimationMode {
+    // start again from the first frame
+    Loop,
+    // play the frames backwards, then forwards again
+    PingPong,
+    // stay on the last frame
+    Once,
+}
+
+// AnimatedSprite steps through frames of a sprite sheet, each frame being
+// shown for its own duration.
+#[derive(Debug, Clone)]
+pub struct AnimatedSprite {
+    // sprite sheet frame index and duration
+    frames: Vec<(usize, Duration)>,
+    mode: AnimationMode,
+    current: usize,
+    elapsed: Duration,
+    forward: bool,
+    finished: bool,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl AnimatedSprite {
+    // frames of the same duration
+    pub fn new(frames: &[usize], frame_duration: Duration, mode: AnimationMode) -> Self {
+        Self::with_durations(
+            frames
+                .iter()
+                .map(|&frame| (frame, frame_duration))
+                .collect(),
+            mode,
+        )
+    }
+
+    pub fn with_durations(frames: Vec<(usize, Duration)>, mode: AnimationMode) -> Self {
+        Self {
+            frames: frames
+                .into_iter()
+                .map(|(frame, duration)| (frame, duration.max(MIN_FRAME_DURATION)))
+                .collect(),
+            mode,
+            current: 0,
+            elapsed: Duration::ZERO,
+            forward: true,
+            finished: false,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    pub fn with_flip(mut self, horizontal: bool, vertical: bool) -> Self {
+        self.flip_x = horizontal;
+        self.flip_y = vertical;
+        self
+    }
+
+    pub fn update(&mut self, dt: Duration) {
+        if self.frames.is_empty() || self.finished {
+            return;
+        }
+        self.elapsed += dt;
+        while self.elapsed >= self.frames[self.current].1 {
+            self.elapsed -= self.frames[self.current].1;
+            if !self.advance() {
+                self.finished = true;
+                self.elapsed = Duration::ZERO;
+                break;
+            }
+        }
+    }
+
+    // move to the next frame, false when a one-shot animation is over
+    fn advance(&mut self) -> bool {
+        let last = self.frames.len() - 1;
+        match self.mode {
+            AnimationMode::Loop => {
+                self.current = if self.current == last {
+                    0
+                } else {
+                    self.current + 1
+                };
+                true
+            }
+            AnimationMode::Once if self.current == last => false,
+            AnimationMode::Once => {
+                self.current += 1;
+                true
+            }
+            AnimationMode::PingPong if last == 0 => true,
+            AnimationMode::PingPong => {
+                if self.current == last {
+                    self.forward = false;
+                } else if self.current == 0 {
+                    self.forward = true;
+                }
+                if self.forward {
+                    self.current += 1;
+                } else {
+                    self.current -= 1;
+                }
+                true
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.current = 0;
+        self.elapsed = Duration::ZERO;
+        self.forward = true;
+        self.finished = false;
+    }
+
+    // sprite sheet index of the current frame
+    pub fn frame(&self) -> usize {
+        self.frames.get(self.current).map_or(0, |(frame, _)| *frame)
+    }
+
+    // only one-shot animations finish
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // area of the sheet texture to draw, with the flips applied
+    pub fn uv(&self, sheet: &SpriteSheet) -> UvRect {
+        sheet
+            .frame(self.frame())
+            .unwrap_or(UvRect::FULL)
+            .flipped(self.flip_x, self.flip_y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: Duration = Duration::from_millis(100);
+
+    fn play(sprite: &mut AnimatedSprite, steps: usize) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                sprite.update(FRAME);
+                sprite.frame()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn grid_and_flip() {
+        let sheet = SpriteSheet::grid(TextureHandle::WHITE, [66, 32], [32, 16], 2);
+        assert_eq!(sheet.len(), 2);
+        assert_eq!(
+            sheet.frame(1),
+            Some(UvRect::from_pixels([66, 32], 34, 0, 32, 16))
+        );
+
+        let uv = UvRect::FULL.flipped(true, false);
+        assert_eq!(uv.x, 1.0);
+        assert_eq!(uv.width, -1.0);
+        assert_eq!(uv.y, 0.0);
+    }
+
+    #[test]
+    fn animation_modes() {
+        let frames = [4, 5, 6];
+        let mut sprite = AnimatedSprite::new(&frames, FRAME, AnimationMode::Loop);
+        assert_eq!(play(&mut sprite, 4), [5, 6, 4, 5]);
+
+        let mut sprite = AnimatedSprite::new(&frames, FRAME, AnimationMode::PingPong);
+        assert_eq!(play(&mut sprite, 5), [5, 6, 5, 4, 5]);
+
+        let mut sprite = AnimatedSprite::new(&frames, FRAME, AnimationMode::Once);
+        assert_eq!(play(&mut sprite, 3), [5, 6, 6]);
+        assert!(sprite.is_finished());
+
+        // a long update skips frames
+        let mut sprite = AnimatedSprite::new(&frames, FRAME, AnimationMode::Loop);
+        sprite.update(FRAME * 4 + FRAME / 2);
+        assert_eq!(sprite.frame(), 5);
+    }
+}
@@ -0,0 +1,109 @@
+use cgmath::{Vector2, Zero};
+
+use super::camera::Rect;
+
+// Transform2D places a quad in the world: the pivot of the quad is moved to
+// the position and the quad rotates around it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform2D {
+    pub position: Vector2<f32>,
+    pub size: Vector2<f32>,
+    // counter-clockwise, in radians
+    pub rotation: f32,
+    // point of the quad, as a fraction of its size from its center: (0, 0) is
+    // the center, (-0.5, -0.5) the bottom-left corner
+    pub pivot: Vector2<f32>,
+    // depth of the quad, quads with a greater z are drawn in front of the
+    // others whatever the order they are submitted in. The default camera
+    // sees from z = 1.9 (closest) to z = -8 (farthest).
+    pub z: f32,
+    // only the cameras whose layer mask contains the layer draw the quad,
+    // layers go from 0 to 31
+    pub layer: u32,
+}
+
+impl Transform2D {
+    pub fn new(position: Vector2<f32>, size: Vector2<f32>) -> Self {
+        Self {
+            position,
+            size,
+            rotation: 0.0,
+            pivot: Vector2::zero(),
+            z: 0.0,
+            layer: 0,
+        }
+    }
+
+    pub fn with_rotation(mut self, radians: f32) -> Self {
+        self.rotation = radians;
+        self
+    }
+
+    pub fn with_pivot(mut self, pivot: Vector2<f32>) -> Self {
+        self.pivot = pivot;
+        self
+    }
+
+    pub fn with_z(mut self, z: f32) -> Self {
+        self.z = z;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: u32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    // world position of a point of the quad, expressed like the pivot
+    pub fn apply(&self, point: Vector2<f32>) -> Vector2<f32> {
+        let local = Vector2::new(
+            (point.x - self.pivot.x) * self.size.x,
+            (point.y - self.pivot.y) * self.size.y,
+        );
+        let (sin, cos) = self.rotation.sin_cos();
+        self.position + Vector2::new(local.x * cos - local.y * sin, local.x * sin + local.y * cos)
+    }
+
+    pub fn corners(&self) -> [Vector2<f32>; 4] {
+        [
+            Vector2::new(-0.5, 0.5),
+            Vector2::new(0.5, 0.5),
+            Vector2::new(0.5, -0.5),
+            Vector2::new(-0.5, -0.5),
+        ]
+        .map(|corner| self.apply(corner))
+    }
+
+    // smallest axis aligned rectangle containing the quad
+    pub fn bounds(&self) -> Rect {
+        if self.rotation == 0.0 && self.pivot.is_zero() {
+            let half_size = Vector2::new(self.size.x.abs(), self.size.y.abs()) / 2.0;
+            return Rect::new(self.position - half_size, self.position + half_size);
+        }
+        Rect::from_points(&self.corners()).expect("a quad has corners")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use cgmath::InnerSpace;
+
+    use super::*;
+
+    #[test]
+    fn rotation_around_pivot() {
+        // rotate a 2x1 quad a quarter turn around its bottom-left corner
+        let transform = Transform2D::new(Vector2::new(1.0, 1.0), Vector2::new(2.0, 1.0))
+            .with_rotation(FRAC_PI_2)
+            .with_pivot(Vector2::new(-0.5, -0.5));
+
+        let bottom_right = transform.apply(Vector2::new(0.5, -0.5));
+        assert!((bottom_right - Vector2::new(1.0, 3.0)).magnitude2() < 1e-6);
+
+        let bounds = transform.bounds();
+        assert!((bounds.min - Vector2::new(0.0, 1.0)).magnitude2() < 1e-6);
+        assert!((bounds.max - Vector2::new(1.0, 3.0)).magnitude2() < 1e-6);
+    }
+}
@@ -0,0 +1,54 @@
+use winit::{
+    dpi::{LogicalSize, PhysicalSize},
+    event::{Event, WindowEvent},
+};
+
+// WindowState keeps track of the window dimensions and scale factor.
+//
+// The swapchain works with physical pixels while everything the application
+// sees (camera, mouse coordinates) works with logical pixels, so that a
+// monitor change does not alter what ends up on screen.
+#[derive(Debug, Copy, Clone)]
+pub struct WindowState {
+    physical_size: PhysicalSize<u32>,
+    scale_factor: f64,
+}
+
+impl WindowState {
+    pub fn new(physical_size: PhysicalSize<u32>, scale_factor: f64) -> Self {
+        Self {
+            physical_size,
+            scale_factor,
+        }
+    }
+
+    pub fn on_event(&mut self, event: &Event<()>) {
+        if let Event::WindowEvent { ref event, .. } = event {
+            match event {
+                WindowEvent::Resized(size) => {
+                    self.physical_size = *size;
+                }
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    new_inner_size,
+                } => {
+                    self.scale_factor = *scale_factor;
+                    self.physical_size = **new_inner_size;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    pub fn physical_size(&self) -> PhysicalSize<u32> {
+        self.physical_size
+    }
+
+    pub fn logical_size(&self) -> LogicalSize<f32> {
+        self.physical_size.to_logical(self.scale_factor)
+    }
+}